@@ -0,0 +1,130 @@
+//! check the args of `cl_format!` against the literal control string at compile time
+
+use cl_format_parser::*;
+
+/// The traits of the args used by tildes in order. The args after them are unknown at compile
+/// time when it returns false, like `~@{` uses all the rest args and `~^` may end early.
+pub(crate) fn args_traits(tildes: &[Tilde], traits: &mut Vec<&'static str>) -> bool {
+    for t in tildes {
+        // the `V` parameters take the args before the tilde
+        if !t.params().is_empty() {
+            return false;
+        }
+
+        match t.value() {
+            TildeKind::Char(_) => traits.push("TildeKindChar"),
+            TildeKind::Float(_) => traits.push("TildeKindFloat"),
+            TildeKind::Exponential(_) => traits.push("TildeKindExponential"),
+            TildeKind::General(_) => traits.push("TildeKindGeneral"),
+            TildeKind::Monetary(_) => traits.push("TildeKindMonetary"),
+            TildeKind::Radix(_) => traits.push("TildeKindRadix"),
+            TildeKind::Digit(_) => traits.push("TildeKindDigit"),
+            TildeKind::Binary(_) => traits.push("TildeKindBinary"),
+            TildeKind::Octal(_) => traits.push("TildeKindOctal"),
+            TildeKind::Hex(_) => traits.push("TildeKindHex"),
+            TildeKind::Va(_) => traits.push("TildeKindVa"),
+            TildeKind::Standard(_) => traits.push("TildeKindStandard"),
+            // ~:P backs up to the last arg
+            TildeKind::Plural((false, _)) => traits.push("TildeKindPlural"),
+            TildeKind::Plural((true, _)) => (),
+            TildeKind::Loop((body, TildeLoopKind::Nil | TildeLoopKind::NilColon, _))
+            | TildeKind::Loop((body, TildeLoopKind::Colon, _)) => {
+                if body.is_empty() {
+                    // the body is the control string arg
+                    traits.push("TildeKindRecursive");
+                }
+                traits.push("TildeKindLoop")
+            }
+            TildeKind::Recursive(false) => {
+                traits.push("TildeKindRecursive");
+                traits.push("TildeKindLoop")
+            }
+            TildeKind::Recursive(true) => {
+                traits.push("TildeKindRecursive");
+                return false;
+            }
+            TildeKind::Cond((_, TildeCondKind::Nil(_) | TildeCondKind::Colon)) => {
+                // the clauses may use different args
+                traits.push("TildeKindCond");
+                return false;
+            }
+            TildeKind::Justify((segments, _, _, _, None)) => {
+                if !args_traits(segments, traits) {
+                    return false;
+                }
+            }
+            TildeKind::CaseConv((body, _)) | TildeKind::VecTilde(body) => {
+                if !args_traits(body, traits) {
+                    return false;
+                }
+            }
+            TildeKind::Text(_)
+            | TildeKind::Tildes(_)
+            | TildeKind::Newline(_)
+            | TildeKind::FreshLine(_)
+            | TildeKind::Page(_)
+            | TildeKind::Tabulate(_) => (),
+            TildeKind::Loop(_)
+            | TildeKind::Star(_)
+            | TildeKind::LoopEnd(_)
+            | TildeKind::Cond(_)
+            | TildeKind::Justify(_) => return false,
+        }
+    }
+
+    true
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn traits_of(s: &str) -> (Vec<&'static str>, bool) {
+        let tildes = Tilde::scan(s)
+            .unwrap()
+            .into_iter()
+            .map(|(_, t)| t)
+            .collect::<Vec<_>>();
+        let mut traits = vec![];
+        let exact = args_traits(&tildes, &mut traits);
+        (traits, exact)
+    }
+
+    #[test]
+    fn test_args_traits() {
+        assert_eq!(
+            traits_of("~a, ~d~%~5,2F ~:P"),
+            (
+                vec!["TildeKindVa", "TildeKindDigit", "TildeKindFloat"],
+                true
+            )
+        );
+
+        assert_eq!(
+            traits_of("~{~a~^, ~} ~?"),
+            (
+                vec!["TildeKindLoop", "TildeKindRecursive", "TildeKindLoop"],
+                true
+            )
+        );
+
+        assert_eq!(
+            traits_of("~:(~a ~c~)~<~s~;~x~>"),
+            (
+                vec![
+                    "TildeKindVa",
+                    "TildeKindChar",
+                    "TildeKindStandard",
+                    "TildeKindHex"
+                ],
+                true
+            )
+        );
+
+        // unknown after them
+        assert_eq!(traits_of("~a~^~a"), (vec!["TildeKindVa"], false));
+        assert_eq!(traits_of("~a ~@{~a~} ~a"), (vec!["TildeKindVa"], false));
+        assert_eq!(traits_of("~[a~;~a~] ~a"), (vec!["TildeKindCond"], false));
+        assert_eq!(traits_of("~v,'0d"), (vec![], false));
+    }
+}
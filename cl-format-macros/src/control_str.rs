@@ -0,0 +1,376 @@
+//! `control_str!` parses the control string literal at compile time and makes the
+//! expression building the same tildes in cl-format.
+
+use crate::check_args::args_traits;
+use cl_format_parser::*;
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use std::borrow::Cow;
+use syn::{spanned::Spanned, Expr, LitStr, Path};
+
+/// make the `&'static ControlStr<'static>` of the control string literal, `krate` is the path
+/// of cl-format
+pub(crate) fn expand(krate: &Path, lit: &LitStr) -> syn::Result<TokenStream> {
+    let tildes = scan(lit)?;
+    Ok(control_str_expr(krate, lit, &tildes))
+}
+
+/// `cl_format!` of the literal control string, the args are checked with the tildes
+pub(crate) fn expand_cl_format(
+    krate: &Path,
+    lit: &LitStr,
+    args: &[Expr],
+) -> syn::Result<TokenStream> {
+    let tildes = scan(lit)?;
+    let mut traits = vec![];
+    let exact = args_traits(
+        &tildes.iter().map(|(_, t)| t.clone()).collect::<Vec<_>>(),
+        &mut traits,
+    );
+
+    if args.len() < traits.len() {
+        return Err(syn::Error::new(
+            lit.span(),
+            format!(
+                "the control string needs {}{}, but {} given",
+                if exact { "" } else { "at least " },
+                count_args(traits.len()),
+                count_args(args.len())
+            ),
+        ));
+    }
+    if exact && args.len() > traits.len() {
+        return Err(syn::Error::new(
+            arg_span(&args[traits.len()]),
+            format!(
+                "the control string needs {}, but {} given",
+                count_args(traits.len()),
+                count_args(args.len())
+            ),
+        ));
+    }
+
+    let control_str = control_str_expr(krate, lit, &tildes);
+    if args.is_empty() {
+        return Ok(quote!((#control_str).reveal(#krate::Args::new(::std::vec![]))));
+    }
+
+    // the args are bound once, and their names point to themselves in the error messages
+    let names = args
+        .iter()
+        .enumerate()
+        .map(|(i, a)| {
+            Ident::new(
+                &format!("arg{}", i),
+                Span::mixed_site().located_at(arg_span(a)),
+            )
+        })
+        .collect::<Vec<_>>();
+    let checks = traits.iter().zip(&names).map(|(tr, name)| {
+        let tr = Ident::new(tr, Span::call_site());
+        quote! {
+            {
+                fn check<T: ?Sized + #krate::#tr>(_: &T) {}
+                check(#name);
+            }
+        }
+    });
+
+    Ok(quote! {
+        match (#(#args,)*) {
+            (#(#names,)*) => {
+                #(#checks)*
+                (#control_str).reveal(Into::<#krate::Args<'_, '_>>::into([
+                    #(#names as &dyn #krate::TildeAble),*
+                ]))
+            }
+        }
+    })
+}
+
+fn count_args(n: usize) -> String {
+    format!("{} arg{}", n, if n == 1 { "" } else { "s" })
+}
+
+/// the args from `cl_format!` are in the groups without delimiter
+fn arg_span(arg: &Expr) -> Span {
+    match arg {
+        Expr::Group(g) => arg_span(&g.expr),
+        _ => arg.span(),
+    }
+}
+
+fn scan(lit: &LitStr) -> syn::Result<Vec<((usize, usize), Tilde)>> {
+    let s = lit.value();
+    Tilde::scan(&s).map_err(|(start, e)| {
+        let directive = s[start..].chars().take(10).collect::<String>();
+        syn::Error::new(
+            directive_span(lit, start, start + directive.len()),
+            format!(
+                "cannot parse the directive `{}` at {} of the control string: {}",
+                directive, start, e
+            ),
+        )
+    })
+}
+
+/// the span of `start..end` of the control string, or the whole literal if the compiler cannot
+/// make the subspan or the literal has escapes
+fn directive_span(lit: &LitStr, start: usize, end: usize) -> Span {
+    let token = lit.token();
+    let repr = token.to_string();
+    // the value is the same as the text between the quotes of the plain or raw string
+    let open = repr.find('"').map(|i| i + 1);
+    let close = repr.rfind('"');
+    match (open, close) {
+        (Some(open), Some(close)) if open <= close && repr[open..close] == lit.value() => token
+            .subspan(open + start..open + end)
+            .unwrap_or_else(|| lit.span()),
+        _ => lit.span(),
+    }
+}
+
+/// the static ControlStr, the tildes are made at compile time
+fn control_str_expr(krate: &Path, lit: &LitStr, tildes: &[((usize, usize), Tilde)]) -> TokenStream {
+    let tildes = tildes.iter().map(|((start, end), t)| {
+        let t = t.to_expr(krate);
+        quote!(((#start, #end), #t))
+    });
+
+    quote! {
+        {
+            static CONTROL_STR: #krate::ControlStr<'static> =
+                #krate::ControlStr::from_static(#lit, const { &[#(#tildes),*] });
+            &CONTROL_STR
+        }
+    }
+}
+
+/// the const expression makes the same value in cl-format, `krate` is the path of cl-format
+trait ToExpr {
+    fn to_expr(&self, krate: &Path) -> TokenStream;
+}
+
+macro_rules! literal_to_expr {
+    ($($t:ty),+) => {
+        $(
+            impl ToExpr for $t {
+                fn to_expr(&self, _: &Path) -> TokenStream {
+                    quote!(#self)
+                }
+            }
+        )+
+    };
+}
+
+literal_to_expr!(usize, isize, u8, i64, char, bool);
+
+impl ToExpr for Cow<'static, str> {
+    fn to_expr(&self, _: &Path) -> TokenStream {
+        let s = self.as_ref();
+        quote!(::std::borrow::Cow::Borrowed(#s))
+    }
+}
+
+impl<T: ToExpr + Clone> ToExpr for Cow<'static, [T]> {
+    fn to_expr(&self, krate: &Path) -> TokenStream {
+        slice_to_expr(self, krate)
+    }
+}
+
+/// the borrowed slice in the static
+fn slice_to_expr<T: ToExpr>(vv: &[T], krate: &Path) -> TokenStream {
+    let vv = vv.iter().map(|v| v.to_expr(krate));
+    quote!(::std::borrow::Cow::Borrowed(const { &[#(#vv),*] }))
+}
+
+impl<T: ToExpr> ToExpr for Option<T> {
+    fn to_expr(&self, krate: &Path) -> TokenStream {
+        match self {
+            Some(v) => {
+                let v = v.to_expr(krate);
+                quote!(::std::option::Option::Some(#v))
+            }
+            None => quote!(::std::option::Option::None),
+        }
+    }
+}
+
+macro_rules! tuple_to_expr {
+    ($(($($t:ident),+)),+) => {
+        $(
+            #[allow(non_snake_case)]
+            impl<$($t: ToExpr),+> ToExpr for ($($t,)+) {
+                fn to_expr(&self, krate: &Path) -> TokenStream {
+                    let ($($t,)+) = self;
+                    $(let $t = $t.to_expr(krate);)+
+                    quote!(($(#$t,)+))
+                }
+            }
+        )+
+    };
+}
+
+tuple_to_expr!(
+    (A, B),
+    (A, B, C),
+    (A, B, C, D),
+    (A, B, C, D, E),
+    (A, B, C, D, E, F),
+    (A, B, C, D, E, F, G),
+    (A, B, C, D, E, F, G, H)
+);
+
+/// the variants with one field in `[]`, the variants without field in `()`
+macro_rules! enum_to_expr {
+    ($ty:ident, [$($v:ident),*], ($($unit:ident),*)) => {
+        impl ToExpr for $ty {
+            fn to_expr(&self, krate: &Path) -> TokenStream {
+                match self {
+                    $(
+                        $ty::$v(field) => {
+                            let field = field.to_expr(krate);
+                            quote!(#krate::$ty::$v(#field))
+                        }
+                    )*
+                    $($ty::$unit => quote!(#krate::$ty::$unit),)*
+                }
+            }
+        }
+    };
+}
+
+enum_to_expr!(CharKind, [], (Nil, At, Colon, AtColon));
+enum_to_expr!(RadixFlag, [], (At, Colon, AtColon));
+enum_to_expr!(TildeCondKind, [Nil], (Sharp, At, Colon));
+enum_to_expr!(TildeLoopKind, [], (Nil, NilColon, At, Colon, AtColon));
+enum_to_expr!(
+    CaseConvKind,
+    [],
+    (Lower, Capitalize, CapitalizeFirst, Upper)
+);
+enum_to_expr!(TildeParam, [], (Arg, ArgsCount));
+enum_to_expr!(StarKind, [Hop, Skip, Goto], ());
+enum_to_expr!(
+    TildeKind,
+    [
+        Char,
+        Float,
+        Exponential,
+        General,
+        Monetary,
+        Radix,
+        Digit,
+        Binary,
+        Octal,
+        Hex,
+        Va,
+        Star,
+        Standard,
+        Loop,
+        Recursive,
+        Plural,
+        Justify,
+        CaseConv,
+        LoopEnd,
+        Tildes,
+        Newline,
+        FreshLine,
+        Page,
+        Tabulate,
+        Cond,
+        Text,
+        VecTilde
+    ],
+    ()
+);
+
+impl ToExpr for Tilde {
+    fn to_expr(&self, krate: &Path) -> TokenStream {
+        let len = self.len();
+        let value = self.value().to_expr(krate);
+        if self.params().is_empty() {
+            quote!(#krate::Tilde::new(#len, #value))
+        } else {
+            let params = slice_to_expr(self.params(), krate);
+            quote!(#krate::Tilde::with_params(#len, #value, #params))
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_expand() -> syn::Result<()> {
+        let krate: Path = syn::parse_quote!(cl_format);
+        let lit: LitStr = syn::parse_quote!("a~2{~a~}");
+        let expr = expand(&krate, &lit)?.to_string();
+        let va = quote!(cl_format::TildeKind::Va(::std::option::Option::None));
+        assert!(expr.contains(
+            &quote!(cl_format::TildeKind::Loop((
+                ::std::borrow::Cow::Borrowed(
+                    const {
+                        &[cl_format::Tilde::new(2usize, #va)]
+                    }
+                ),
+                cl_format::TildeLoopKind::Nil,
+                ::std::option::Option::Some(2usize),
+            )))
+            .to_string()
+        ));
+
+        let lit: LitStr = syn::parse_quote!("~a ~{~a");
+        let e = expand(&krate, &lit).unwrap_err();
+        assert!(e.to_string().contains("`~{~a` at 3"));
+
+        // the ~: tildes inside ~[ are not ~:;
+        for case in ["~[zero~;~:d~]", "~:[no~;~:d~]", "~[~:(abc~)~]", "~@[~:C~]"] {
+            let lit = LitStr::new(case, proc_macro2::Span::call_site());
+            assert!(expand(&krate, &lit).is_ok(), "{case}");
+        }
+
+        let lit: LitStr = syn::parse_quote!("~a ~[a~:");
+        let e = expand(&krate, &lit).unwrap_err();
+        assert!(e.to_string().contains("`~[a~:` at 3"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand_cl_format() -> syn::Result<()> {
+        let krate: Path = syn::parse_quote!(cl_format);
+        let lit: LitStr = syn::parse_quote!("~a and ~d");
+        let args: Vec<Expr> = vec![syn::parse_quote!(&1), syn::parse_quote!(&2)];
+        let expr = expand_cl_format(&krate, &lit, &args)?.to_string();
+        assert!(expr.contains(&quote!(cl_format::TildeKindVa).to_string()));
+        assert!(expr.contains(&quote!(cl_format::TildeKindDigit).to_string()));
+
+        assert_eq!(
+            expand_cl_format(&krate, &lit, &args[..1])
+                .unwrap_err()
+                .to_string(),
+            "the control string needs 2 args, but 1 arg given"
+        );
+
+        let lit: LitStr = syn::parse_quote!("~a");
+        assert_eq!(
+            expand_cl_format(&krate, &lit, &args)
+                .unwrap_err()
+                .to_string(),
+            "the control string needs 1 arg, but 2 args given"
+        );
+
+        // the args of ~@{ are unknown
+        let lit: LitStr = syn::parse_quote!("~a~@{~a~}");
+        assert!(expand_cl_format(&krate, &lit, &args).is_ok());
+        assert_eq!(
+            expand_cl_format(&krate, &lit, &[]).unwrap_err().to_string(),
+            "the control string needs at least 1 arg, but 0 args given"
+        );
+
+        Ok(())
+    }
+}
@@ -1,9 +1,9 @@
 #![doc = r#"The macros here should auto generate several traits and the major TildeAble trait.
 
-For example:
+For example, the hidden `TildeKindTraits` derive on the directive list of cl-format, which mirrors the `TildeKind` enum of cl-format-parser:
 
 ```rust
-#[derive(Debug, PartialEq, TildeAble)]
+#[derive(Debug, PartialEq, TildeKindTraits)]
 pub enum TildeKind {
     /// ~C ~:C
     #[implTo(char)]
@@ -76,7 +76,18 @@ trait TildeKindVa {
     }
 }
 
+// and every trait is implemented for `dyn TildeAble` by its `into_tildekind_*` method
+impl TildeKindVa for dyn TildeAble + '_ {
+    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
+        self.into_tildekind_va().ok_or_else(...)?.format(tkind, buf)
+    }
+}
+
 ```
+
+The `format` of the variant with `#[reveal]` returns `Result<Reveal, TildeError>`, for the tildes
+revealing other tildes, like `~^` inside them escapes upward. The trait of the variant with
+`#[handwritten]` is written by hand in cl-format, only the `into_tildekind_*` method is generated.
 "#]
 
 use std::{collections::HashMap, error::Error};
@@ -84,11 +95,44 @@ use std::{collections::HashMap, error::Error};
 use proc_macro::TokenStream;
 use proc_macro2::{Ident, Literal, Span};
 use quote::quote;
-use syn::{parse_macro_input, spanned::Spanned, Attribute, Data, DataEnum, DeriveInput, Variant};
-
-#[proc_macro_derive(TildeAble, attributes(implTo))]
+use syn::{
+    parse::{Parse, ParseStream},
+    parse_macro_input,
+    spanned::Spanned,
+    Attribute, Data, DataEnum, DeriveInput, Expr, LitStr, Path, Token, Variant,
+};
+
+mod check_args;
+mod control_str;
+mod tilde_able;
+
+/// Derive `TildeAble` of the user type with the `#[tilde(...)]` attributes.
+///
+/// The key is the directive, like `va` of `~a` and `digit` of `~d`. The value is `"Display"`
+/// or `"Debug"` (only for `va` and `standard`), or the field revealed by the same directive.
+///
+/// ```ignore
+/// #[derive(Debug, TildeAble)]
+/// #[tilde(va = "Display", standard = "Debug", digit = a)]
+/// struct MyStruct {
+///     a: usize,
+///     b: String,
+/// }
+/// ```
+#[proc_macro_derive(TildeAble, attributes(tilde))]
 pub fn derive_tilde_able(input: TokenStream) -> TokenStream {
     let input = parse_macro_input!(input as DeriveInput);
+    tilde_able::expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// Generate the `TildeAble` trait and the `TildeKind*` traits of the directive list in cl-format,
+/// see the crate doc.
+#[doc(hidden)]
+#[proc_macro_derive(TildeKindTraits, attributes(implTo, reveal, handwritten))]
+pub fn derive_tilde_kind_traits(input: TokenStream) -> TokenStream {
+    let input = parse_macro_input!(input as DeriveInput);
 
     let mut return_types_traits = vec![];
     let mut all_default_methods = vec![];
@@ -98,7 +142,7 @@ pub fn derive_tilde_able(input: TokenStream) -> TokenStream {
         Data::Enum(DataEnum { ref variants, .. }) => {
             let all_vars = variants.iter().map(|var| parse_variant_attrs(var));
 
-            all_vars.for_each(|(field, tys)| {
+            all_vars.for_each(|(field, tys, (reveal, handwritten))| {
                 let fname = Ident::new(
                     &(String::from("into_tildekind_") + &field.to_lowercase()),
                     Span::call_site(),
@@ -124,16 +168,40 @@ pub fn derive_tilde_able(input: TokenStream) -> TokenStream {
 
                 //
                 let doc = Literal::string(&(return_type.to_string() + " is the trait that contains implementation of type for TildeKind::"+ &field + ".\n\nGenerated by cl-format-macro"));
+                let unimplemented = Literal::string(&(String::from("`{Self}` cannot be revealed by the TildeKind::") + &field + " directive"));
+                let note = Literal::string(&(String::from("implement `") + &return_type.to_string() + "` for `{Self}` and return it in `TildeAble::" + &fname.to_string() + "`"));
+                if handwritten {
+                    return;
+                }
+
+                let missing = Literal::string(&(String::from("cannot reveal to ") + &field));
+                // the tildes revealing other tildes tell how the rest go on
+                let output = if reveal { quote!(Reveal) } else { quote!(()) };
                 return_types_traits.push(quote! {
                     #[doc = #doc]
+                    #[diagnostic::on_unimplemented(message = #unimplemented, note = #note)]
                     pub trait #return_type: Debug {
-                        fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
-                            Err(TildeError::new(ErrorKind::EmptyImplenmentError, "haven't implenmented yet").into(),)
+                        fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<#output, TildeError> {
+                            Err(TildeError::new(ErrorKind::EmptyImplenmentError, "haven't implenmented yet"),)
                         }
-                }})
+                }
+
+                    /// the type behind `dyn TildeAble` is only known at runtime
+                    impl #return_type for dyn TildeAble + '_ {
+                        fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<#output, TildeError> {
+                            self.#fname()
+                                .ok_or_else(|| TildeError::new(ErrorKind::RevealError, #missing))?
+                                .format(tkind, buf)
+                        }
+                    }
+                })
             });
         }
-        _ => panic!("only support the enum"),
+        _ => {
+            return syn::Error::new(input.ident.span(), "TildeKindTraits is for the enum only")
+                .into_compile_error()
+                .into()
+        }
     };
 
     let mut result = vec![];
@@ -171,8 +239,77 @@ pub fn derive_tilde_able(input: TokenStream) -> TokenStream {
     proc_macro2::TokenStream::from_iter(result.into_iter()).into()
 }
 
-/// return the field Ident and all types implTo. Empty if there is no implTo types
-fn parse_variant_attrs(variant: &Variant) -> (String, impl Iterator<Item = Ident> + '_) {
+/// Parse the control string literal at compile time, the bad control string is a compile error.
+///
+/// It takes the path of cl-format and the literal, `control_str!` of cl-format passes `$crate`.
+/// It returns the `&'static ControlStr<'static>` which is made at compile time.
+#[doc(hidden)]
+#[proc_macro]
+pub fn control_str_literal(input: TokenStream) -> TokenStream {
+    let ControlStrInput { krate, lit } = parse_macro_input!(input as ControlStrInput);
+    control_str::expand(&krate, &lit)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// `cl_format!` of the literal control string, the args are checked at compile time.
+///
+/// The number of args has to be the same as the tildes use, or at least the args before
+/// the tildes using the args unknown at compile time, like `~@{`. Every arg has to implement
+/// the trait of its tilde, like `TildeKindDigit` of `~d`.
+#[doc(hidden)]
+#[proc_macro]
+pub fn cl_format_literal(input: TokenStream) -> TokenStream {
+    let ClFormatInput {
+        control_str: ControlStrInput { krate, lit },
+        args,
+    } = parse_macro_input!(input as ClFormatInput);
+    control_str::expand_cl_format(&krate, &lit, &args)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+/// the path of cl-format and the literal control string
+struct ControlStrInput {
+    krate: Path,
+    lit: LitStr,
+}
+
+impl Parse for ControlStrInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let krate = input.parse()?;
+        input.parse::<Token![,]>()?;
+        let lit = input.parse()?;
+        Ok(Self { krate, lit })
+    }
+}
+
+/// the path of cl-format, the literal control string, and the args
+struct ClFormatInput {
+    control_str: ControlStrInput,
+    args: Vec<Expr>,
+}
+
+impl Parse for ClFormatInput {
+    fn parse(input: ParseStream) -> syn::Result<Self> {
+        let control_str = input.parse()?;
+        let mut args = vec![];
+        while !input.is_empty() {
+            input.parse::<Token![,]>()?;
+            if input.is_empty() {
+                break;
+            }
+            args.push(input.parse()?);
+        }
+        Ok(Self { control_str, args })
+    }
+}
+
+/// return the field Ident, all types implTo, and (if the trait returns the `Reveal`, if the trait
+/// is written by hand). Empty if there is no implTo types
+fn parse_variant_attrs(
+    variant: &Variant,
+) -> (String, impl Iterator<Item = Ident> + '_, (bool, bool)) {
     let all_impl_to_type = variant
         .attrs
         .iter()
@@ -181,8 +318,13 @@ fn parse_variant_attrs(variant: &Variant) -> (String, impl Iterator<Item = Ident
         .flatten();
 
     let field = variant.ident.to_string();
+    let has_attr = |name| variant.attrs.iter().any(|attr| attr.path().is_ident(name));
 
-    (field.clone(), all_impl_to_type)
+    (
+        field.clone(),
+        all_impl_to_type,
+        (has_attr("reveal"), has_attr("handwritten")),
+    )
 }
 
 /// parse the `implTo` attribute
@@ -272,6 +414,19 @@ mod tests {
         let mut result = parse_variant_attrs(&test_case);
         assert_eq!(result.0, "B");
         assert_eq!(result.1.next(), None);
+        assert_eq!(result.2, (false, false));
+
+        let test_case: Variant = parse_quote! {
+            #[reveal]
+            C
+        };
+        assert_eq!(parse_variant_attrs(&test_case).2, (true, false));
+
+        let test_case: Variant = parse_quote! {
+            #[handwritten]
+            D
+        };
+        assert_eq!(parse_variant_attrs(&test_case).2, (false, true));
 
         Ok(())
     }
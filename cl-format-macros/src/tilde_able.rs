@@ -0,0 +1,255 @@
+//! `#[derive(TildeAble)]` of the user types, the directives are picked by the `#[tilde(...)]`
+//! attributes, like `#[tilde(va = "Display", standard = "Debug", digit = a)]`.
+
+use proc_macro2::{Ident, Span, TokenStream};
+use quote::quote;
+use syn::{DeriveInput, LitStr, Member};
+
+/// the directives can be derived, (the key in `#[tilde(...)]`, the TildeKind variant)
+const DIRECTIVES: [(&str, &str); 14] = [
+    ("char", "Char"),
+    ("float", "Float"),
+    ("exponential", "Exponential"),
+    ("general", "General"),
+    ("monetary", "Monetary"),
+    ("radix", "Radix"),
+    ("digit", "Digit"),
+    ("binary", "Binary"),
+    ("octal", "Octal"),
+    ("hex", "Hex"),
+    ("va", "Va"),
+    ("standard", "Standard"),
+    ("plural", "Plural"),
+    ("cond", "Cond"),
+];
+
+/// how the directive reveals the type
+#[derive(Debug, PartialEq)]
+enum Delegate {
+    /// `"Display"`, push the `{}` of the type
+    Display,
+    /// `"Debug"`, push the `{:?}` of the type
+    Debug,
+    /// the field revealed by the same directive
+    Field(Member),
+}
+
+pub(crate) fn expand(input: &DeriveInput) -> syn::Result<TokenStream> {
+    let directives = parse_tilde_attrs(input)?;
+
+    let ty = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let methods = directives.iter().map(|(key, variant, _)| {
+        let fname = Ident::new(&format!("into_tildekind_{}", key), Span::call_site());
+        let tr = Ident::new(&format!("TildeKind{}", variant), Span::call_site());
+        quote! {
+            fn #fname(&self) -> ::std::option::Option<&dyn cl_format::#tr> {
+                ::std::option::Option::Some(self)
+            }
+        }
+    });
+
+    let trait_impls = directives.iter().map(|(_, variant, delegate)| {
+        let tr = Ident::new(&format!("TildeKind{}", variant), Span::call_site());
+        let body = match delegate {
+            Delegate::Display => quote! {
+                buf.push_str(&::std::format!("{}", self));
+                ::std::result::Result::Ok(())
+            },
+            Delegate::Debug => quote! {
+                buf.push_str(&::std::format!("{:?}", self));
+                ::std::result::Result::Ok(())
+            },
+            Delegate::Field(field) => quote! {
+                cl_format::#tr::format(&self.#field, tkind, buf)
+            },
+        };
+        quote! {
+            impl #impl_generics cl_format::#tr for #ty #ty_generics #where_clause {
+                #[allow(unused_variables)]
+                fn format(
+                    &self,
+                    tkind: &cl_format::TildeKind,
+                    buf: &mut ::std::string::String,
+                ) -> ::std::result::Result<(), cl_format::TildeError> {
+                    #body
+                }
+            }
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics cl_format::TildeAble for #ty #ty_generics #where_clause {
+            fn len(&self) -> usize {
+                1
+            }
+
+            #(#methods)*
+        }
+
+        #(#trait_impls)*
+    })
+}
+
+/// parse all `#[tilde(...)]` attributes to (key, TildeKind variant, delegate)
+fn parse_tilde_attrs(
+    input: &DeriveInput,
+) -> syn::Result<Vec<(&'static str, &'static str, Delegate)>> {
+    let mut result: Vec<(&'static str, &'static str, Delegate)> = vec![];
+
+    for attr in input
+        .attrs
+        .iter()
+        .filter(|attr| attr.path().is_ident("tilde"))
+    {
+        attr.parse_nested_meta(|meta| {
+            let key = meta
+                .path
+                .get_ident()
+                .map(|i| i.to_string())
+                .unwrap_or_default();
+            let (key, variant) = DIRECTIVES.iter().find(|(k, _)| *k == key).ok_or_else(|| {
+                meta.error(format!(
+                    "unknown directive, it has to be one of: {}",
+                    DIRECTIVES.map(|(k, _)| k).join(", ")
+                ))
+            })?;
+
+            if result.iter().any(|(k, _, _)| k == key) {
+                return Err(meta.error(format!("duplicated directive `{}`", key)));
+            }
+
+            let value = meta.value()?;
+            let delegate = if value.peek(LitStr) {
+                let lit: LitStr = value.parse()?;
+                let delegate = match lit.value().as_str() {
+                    "Display" => Delegate::Display,
+                    "Debug" => Delegate::Debug,
+                    _ => {
+                        return Err(syn::Error::new(
+                            lit.span(),
+                            "expected \"Display\", \"Debug\", or a field",
+                        ))
+                    }
+                };
+
+                // other directives have the params the text cannot handle
+                if *key != "va" && *key != "standard" {
+                    return Err(syn::Error::new(
+                        lit.span(),
+                        format!(
+                            "only `va` and `standard` can use \"{}\", `{}` needs a field",
+                            lit.value(),
+                            key
+                        ),
+                    ));
+                }
+
+                delegate
+            } else {
+                Delegate::Field(value.parse()?)
+            };
+
+            result.push((key, variant, delegate));
+            Ok(())
+        })?;
+    }
+
+    if result.is_empty() {
+        return Err(syn::Error::new(
+            input.ident.span(),
+            "#[tilde(...)] needs at least one directive, like #[tilde(va = \"Display\")]",
+        ));
+    }
+
+    Ok(result)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use syn::parse_quote;
+
+    #[test]
+    fn test_parse_tilde_attrs() -> syn::Result<()> {
+        let input: DeriveInput = parse_quote! {
+            #[tilde(va = "Display", standard = "Debug")]
+            #[tilde(digit = a, hex = 0)]
+            struct A;
+        };
+        assert_eq!(
+            parse_tilde_attrs(&input)?,
+            vec![
+                ("va", "Va", Delegate::Display),
+                ("standard", "Standard", Delegate::Debug),
+                ("digit", "Digit", Delegate::Field(parse_quote!(a))),
+                ("hex", "Hex", Delegate::Field(parse_quote!(0))),
+            ]
+        );
+
+        let input: DeriveInput = parse_quote! {
+            #[tilde(loop = a)]
+            struct A;
+        };
+        assert!(parse_tilde_attrs(&input)
+            .unwrap_err()
+            .to_string()
+            .starts_with("unknown directive"));
+
+        let input: DeriveInput = parse_quote! {
+            #[tilde(va = "Display", va = a)]
+            struct A;
+        };
+        assert_eq!(
+            parse_tilde_attrs(&input).unwrap_err().to_string(),
+            "duplicated directive `va`"
+        );
+
+        let input: DeriveInput = parse_quote! {
+            #[tilde(digit = "Display")]
+            struct A;
+        };
+        assert_eq!(
+            parse_tilde_attrs(&input).unwrap_err().to_string(),
+            "only `va` and `standard` can use \"Display\", `digit` needs a field"
+        );
+
+        let input: DeriveInput = parse_quote! {
+            #[tilde(va = "Dbg")]
+            struct A;
+        };
+        assert!(parse_tilde_attrs(&input).is_err());
+
+        // the TildeKind enum derives TildeKindTraits instead
+        let input: DeriveInput = parse_quote! {
+            enum TildeKind {
+                #[implTo(char)]
+                Char,
+            }
+        };
+        assert!(parse_tilde_attrs(&input)
+            .unwrap_err()
+            .to_string()
+            .starts_with("#[tilde(...)] needs at least one directive"));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_expand() -> syn::Result<()> {
+        let input: DeriveInput = parse_quote! {
+            #[tilde(va = "Display", digit = a)]
+            struct A<T> {
+                a: T,
+            }
+        };
+        let expr = expand(&input)?.to_string();
+        assert!(expr.contains(&quote!(impl<T> cl_format::TildeKindDigit for A<T>).to_string()));
+        assert!(expr.contains(&quote!(fn into_tildekind_va(&self)).to_string()));
+        assert!(expr
+            .contains(&quote!(cl_format::TildeKindDigit::format(&self.a, tkind, buf)).to_string()));
+
+        Ok(())
+    }
+}
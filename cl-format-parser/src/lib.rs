@@ -0,0 +1,2605 @@
+//! The parser of the Common Lisp format control string, it parses the tildes to `TildeKind`.
+//!
+//! It is shared by cl-format revealing the tildes with the args, and cl-format-macros parsing
+//! the literal control string at compile time.
+
+use std::borrow::Cow;
+use std::fmt::Debug;
+use std::io::{BufRead, Cursor, Read, Seek, SeekFrom};
+
+mod tilde_kinds;
+pub use tilde_kinds::*;
+
+#[doc = r"The tilde struct"]
+#[derive(Debug, PartialEq, Clone)]
+pub struct Tilde {
+    len: usize,
+    value: TildeKind,
+    params: Cow<'static, [(usize, TildeParam)]>, // the `V` and `#` parameters omitted in the value
+}
+
+impl Tilde {
+    pub const fn new(len: usize, value: TildeKind) -> Self {
+        Self {
+            len,
+            value,
+            params: Cow::Borrowed(&[]),
+        }
+    }
+
+    /// the tilde with `V` or `#` parameters, they are set to the value when revealing
+    pub const fn with_params(
+        len: usize,
+        value: TildeKind,
+        params: Cow<'static, [(usize, TildeParam)]>,
+    ) -> Self {
+        Self { len, value, params }
+    }
+
+    pub fn len(&self) -> usize {
+        self.len
+    }
+
+    pub fn value(&self) -> &TildeKind {
+        &self.value
+    }
+
+    /// the positions of the `V` and `#` parameters in the value
+    pub fn params(&self) -> &[(usize, TildeParam)] {
+        &self.params
+    }
+
+    /*
+    ===============================
+    parse functions below
+    ===============================
+    */
+
+    /// start from '~' to the key char of tilde kind
+    #[allow(clippy::type_complexity)] // the parser of the tilde kind
+    fn scan_for_kind(
+        c: &mut Cursor<&'_ str>,
+    ) -> Result<
+        Box<dyn for<'a, 'b> Fn(&'a mut std::io::Cursor<&'b str>) -> Result<Tilde, TildeError>>,
+        TildeError,
+    > {
+        let mut buf = [0u8; 1];
+        c.read(&mut buf)
+            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+        if buf[0] != b'~' {
+            return Err(TildeError::new(
+                ErrorKind::ParseError,
+                "should start with ~",
+            ));
+        }
+
+        let mut bucket = vec![b'~'];
+        let mut buf_offset = 1;
+        let mut escape = false;
+
+        loop {
+            if c.read(&mut buf)
+                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?
+                == 0
+                || buf[0] == 0_u8
+            {
+                return Err(TildeError::new(ErrorKind::ParseError, "read to end"));
+            }
+
+            buf_offset += 1;
+            bucket.extend_from_slice(&buf);
+            //dbg!(&bucket);
+            if !escape {
+                match &bucket[..] {
+                    [.., b'\\'] | [.., b'\''] => {
+                        // escape the next one
+                        escape = true;
+                        continue;
+                    }
+                    [.., b'a'] | [.., b'A'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_value));
+                    }
+                    [.., b't' | b'T'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_tabulate));
+                    }
+                    [.., b'?'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_recursive));
+                    }
+                    [.., b'p' | b'P'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_plural));
+                    }
+                    [.., b'<'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_justify));
+                    }
+                    [.., b'('] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_case_conv));
+                    }
+                    [.., b'@', b'{'] | [.., b'{'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_loop));
+                    }
+                    [.., b'$'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_monetary));
+                    }
+                    [.., b'f' | b'F'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_float));
+                    }
+                    [.., b'e' | b'E'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_exponential));
+                    }
+                    [.., b'g' | b'G'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_general));
+                    }
+                    [.., b'd'] | [.., b'D'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_digit));
+                    }
+                    [.., b'b' | b'B'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_binary));
+                    }
+                    [.., b'o' | b'O'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_octal));
+                    }
+                    [.., b'x' | b'X'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_hex));
+                    }
+                    [.., b'#' | b':' | b'@', b'['] | [.., b'['] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_cond));
+                    }
+                    [.., b'^'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_loop_end));
+                    }
+                    [.., b':', b'*'] | [.., b'*'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_star));
+                    }
+                    [.., _, b'~'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_tildes));
+                    }
+                    [.., b'S'] | [.., b's'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_standard));
+                    }
+                    [.., b'@', b'c' | b'C'] | [.., b'C' | b'c'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_char));
+                    }
+                    [.., b'R' | b'r'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_radix));
+                    }
+
+                    [.., b'\n'] => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_tilde_newline));
+                    }
+                    [b'~', n @ .., b'%'] if n.iter().all(u8::is_ascii_digit) => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_newline));
+                    }
+                    [b'~', n @ .., b'&'] if n.iter().all(u8::is_ascii_digit) => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_fresh_line));
+                    }
+                    [b'~', n @ .., b'|'] if n.iter().all(u8::is_ascii_digit) => {
+                        c.seek(SeekFrom::Current(-buf_offset))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
+                        return Ok(Box::new(Self::parse_page));
+                    }
+                    _ => {}
+                }
+            }
+            escape = false;
+        }
+    }
+
+    /// scan all tildes of the control string with their (start, end) in it,
+    /// the error comes with the start of the tilde cannot be parsed
+    #[allow(clippy::type_complexity)] // the span of every tilde, and the start of the bad one
+    pub fn scan(s: &str) -> Result<Vec<((usize, usize), Tilde)>, (usize, TildeError)> {
+        let mut c = Cursor::new(s);
+        let mut buf = vec![];
+        let mut has_read_len = 0;
+        let mut result = vec![];
+
+        loop {
+            c.read_until(b'~', &mut buf).map_err(|e| {
+                (
+                    has_read_len,
+                    TildeError::new(ErrorKind::ParseError, e.to_string()),
+                )
+            })?;
+            match buf.last() {
+                // find the next '~'
+                Some(b'~') => {
+                    has_read_len += buf.len() - 1;
+                    c.seek(SeekFrom::Current(-1)).map_err(|e| {
+                        (
+                            has_read_len,
+                            TildeError::new(ErrorKind::ParseError, e.to_string()),
+                        )
+                    })?;
+                }
+                _ => return Ok(result),
+            }
+
+            let t = Tilde::parse(&mut c).map_err(|e| (has_read_len, e))?;
+            let end_index = has_read_len + t.len();
+
+            result.push(((has_read_len, end_index), t));
+            has_read_len = end_index;
+            buf.clear();
+        }
+    }
+
+    /// cursor should located on '~'
+    pub fn parse(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        //dbg!(&c);
+        if let Some(t) = Self::parse_params(c)? {
+            return Ok(t);
+        }
+
+        let parser = Self::scan_for_kind(c)?;
+        parser(c)
+    }
+
+    /// parse the directive which has `V` or `#` prefix parameters, None if there is no such parameter
+    ///
+    /// the directive is parsed with these parameters omitted, they are set when revealing
+    fn parse_params(c: &mut Cursor<&'_ str>) -> Result<Option<Self>, TildeError> {
+        let start = c.position() as usize;
+        let rest = match c.get_ref().get(start..).and_then(|s| s.strip_prefix('~')) {
+            Some(rest) => rest,
+            None => return Ok(None),
+        };
+
+        // the prefix parameters end at the first char cannot be inside parameters
+        let mut raw = vec![String::new()];
+        let mut prefix_len = rest.len();
+        let mut chars = rest.char_indices();
+        while let Some((i, ch)) = chars.next() {
+            match ch {
+                '\'' => {
+                    raw.last_mut().unwrap().push(ch);
+                    if let Some((_, q)) = chars.next() {
+                        raw.last_mut().unwrap().push(q);
+                    }
+                }
+                '0'..='9' | '+' | '-' | 'v' | 'V' | '#' => raw.last_mut().unwrap().push(ch),
+                ',' => raw.push(String::new()),
+                _ => {
+                    prefix_len = i;
+                    break;
+                }
+            }
+        }
+
+        let rest = &rest[prefix_len..];
+        // ~#[ is the TildeCondKind::Sharp
+        if !raw.iter().any(|p| p == "v" || p == "V" || p == "#")
+            || (raw == ["#"] && rest.starts_with('['))
+        {
+            return Ok(None);
+        }
+
+        let params = raw
+            .iter()
+            .enumerate()
+            .filter_map(|(i, p)| match p.as_str() {
+                "v" | "V" => Some((i, TildeParam::Arg)),
+                "#" => Some((i, TildeParam::ArgsCount)),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+
+        // parse with these parameters omitted
+        let placeholder = raw
+            .iter()
+            .map(|p| match p.as_str() {
+                "v" | "V" | "#" => "",
+                _ => p.as_str(),
+            })
+            .collect::<Vec<_>>()
+            .join(",");
+        let t = Self::parse(&mut Cursor::new(
+            format!("~{}{}", placeholder, rest).as_str(),
+        ))?;
+        let rest_len = t.len() - 1 - placeholder.len();
+
+        let len = 1 + prefix_len + rest_len;
+        c.set_position((start + len) as u64);
+        Ok(Some(Tilde::with_params(len, t.value, params.into())))
+    }
+
+    /// parse function for '~{~}'
+    fn parse_loop(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let whole = Self::read_directive(c, b"{")?;
+        let (params, colon, at) = split_params(&whole[1..whole.len() - 1], 1)?;
+        let max = nth_param(&params, 0)?;
+
+        let loop_kind = match (colon, at) {
+            (false, false) => TildeLoopKind::Nil,
+            (false, true) => TildeLoopKind::At,
+            (true, false) => TildeLoopKind::Colon,
+            (true, true) => TildeLoopKind::AtColon,
+        };
+        let mut total_len = whole.len();
+
+        let mut result = vec![];
+        let mut buf = vec![];
+        let mut char_buf = [0u8; 3];
+
+        loop {
+            // read text until the next '~'
+            c.read_until(b'~', &mut buf)
+                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+
+            match buf.as_slice() {
+                [b'~'] => {
+                    c.seek(SeekFrom::Current(-1))
+                        .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+                }
+                [.., b'~'] => {
+                    c.seek(SeekFrom::Current(-1))
+                        .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+                    result.push(Tilde::new(
+                        buf.len() - 1,
+                        TildeKind::Text(
+                            String::from_utf8(buf[..buf.len() - 1].to_vec())
+                                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?
+                                .into(),
+                        ),
+                    ));
+                    total_len += buf.len() - 1;
+                }
+                [..] => {
+                    return Err(TildeError::new(
+                        ErrorKind::ParseError,
+                        "cannot find the ~} of ~{",
+                    ))
+                }
+            }
+
+            c.read(&mut char_buf)
+                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+
+            match char_buf {
+                [b'~', b'}', 0] => {
+                    return Ok(Tilde::new(
+                        total_len + 2,
+                        TildeKind::Loop((result.into(), loop_kind, max)),
+                    ));
+                }
+                [b'~', b'}', ..] => {
+                    c.seek(SeekFrom::Current(-1))
+                        .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+                    return Ok(Tilde::new(
+                        total_len + 2,
+                        TildeKind::Loop((result.into(), loop_kind, max)),
+                    ));
+                }
+                [b'~', b':', b'}'] => {
+                    return Ok(Tilde::new(
+                        total_len + 3,
+                        TildeKind::Loop((
+                            result.into(),
+                            if loop_kind == TildeLoopKind::Nil {
+                                TildeLoopKind::NilColon
+                            } else {
+                                loop_kind
+                            },
+                            max,
+                        )),
+                    ));
+                }
+                _ => {
+                    let back = 3 - char_buf.iter().filter(|b| **b == 0).count() as i64;
+                    c.seek(SeekFrom::Current(-back))
+                        .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+                }
+            }
+
+            // read the tilde
+            let next = Tilde::parse(c)?;
+            total_len += next.len;
+            result.push(next);
+            char_buf = [0; 3];
+            buf.clear()
+        }
+    }
+
+    /// parse the case conversion `~(...~)`, `~:(...~)`, `~@(...~)`, and `~:@(...~)`
+    fn parse_case_conv(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let start = c.position();
+        let whole = Self::read_directive(c, b"(")?;
+        let (_, colon, at) = split_params(&whole[1..whole.len() - 1], 0)?;
+        let kind = match (colon, at) {
+            (false, false) => CaseConvKind::Lower,
+            (true, false) => CaseConvKind::Capitalize,
+            (false, true) => CaseConvKind::CapitalizeFirst,
+            (true, true) => CaseConvKind::Upper,
+        };
+
+        let mut result = vec![];
+        let mut buf = vec![];
+        let mut char_buf = [0u8; 2];
+        loop {
+            // read text until the next '~'
+            c.read_until(b'~', &mut buf)
+                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+            match buf.as_slice() {
+                [.., b'~'] => {
+                    c.seek(SeekFrom::Current(-1))
+                        .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+                    if buf.len() > 1 {
+                        result.push(Tilde::new(
+                            buf.len() - 1,
+                            TildeKind::Text(
+                                String::from_utf8(buf[..buf.len() - 1].to_vec())
+                                    .map_err(|e| {
+                                        TildeError::new(ErrorKind::ParseError, e.to_string())
+                                    })?
+                                    .into(),
+                            ),
+                        ));
+                    }
+                }
+                _ => {
+                    return Err(TildeError::new(
+                        ErrorKind::ParseError,
+                        "cannot find the ~) of ~(",
+                    ))
+                }
+            }
+            buf.clear();
+
+            c.read_exact(&mut char_buf)
+                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+            if char_buf == *b"~)" {
+                return Ok(Tilde::new(
+                    (c.position() - start) as usize,
+                    TildeKind::CaseConv((result.into(), kind)),
+                ));
+            }
+            c.seek(SeekFrom::Current(-2))
+                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+
+            result.push(Tilde::parse(c)?);
+        }
+    }
+
+    /// parse the tabulate, `~colnum,colincT` and `~colrel,colinc@T`
+    fn parse_tabulate(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let whole = Self::read_directive(c, b"tT")?;
+        let (params, _, at) = split_params(&whole[1..whole.len() - 1], 2)?;
+        Ok(Tilde::new(
+            whole.len(),
+            TildeKind::Tabulate((nth_param(&params, 0)?, nth_param(&params, 1)?, at)),
+        ))
+    }
+
+    /// parse the recursive processing, `~?` and `~@?`
+    fn parse_recursive(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let whole = Self::read_directive(c, b"?")?;
+        let (_, _, at) = split_params(&whole[1..whole.len() - 1], 0)?;
+        Ok(Tilde::new(whole.len(), TildeKind::Recursive(at)))
+    }
+
+    /// parse the plural, `~P`, `~:P`, `~@P`, and `~:@P`
+    fn parse_plural(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let whole = Self::read_directive(c, b"pP")?;
+        let (_, colon, at) = split_params(&whole[1..whole.len() - 1], 0)?;
+        Ok(Tilde::new(whole.len(), TildeKind::Plural((colon, at))))
+    }
+
+    /// parse the justification `~mincol,colinc,minpad,padchar<...~;...~>`
+    ///
+    /// every segment is a `TildeKind::VecTilde`, if the first separator is `~n,m:;`,
+    /// the first segment is the prefix when the line overflows
+    fn parse_justify(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let start = c.position();
+        let whole = Self::read_directive(c, b"<")?;
+        let (params, colon, at) = split_params(&whole[1..whole.len() - 1], 4)?;
+        let padding = (
+            nth_param(&params, 0)?.unwrap_or(0),
+            nth_param(&params, 1)?.unwrap_or(1),
+            nth_param(&params, 2)?.unwrap_or(0),
+            nth_char_param(&params, 3).unwrap_or(' '),
+        );
+
+        let mut segments = vec![];
+        let mut overflow = None;
+        let mut cache = vec![];
+        let mut buf = vec![];
+        loop {
+            // read text until the next '~'
+            c.read_until(b'~', &mut buf)
+                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+            if buf.last() != Some(&b'~') {
+                return Err(TildeError::new(
+                    ErrorKind::ParseError,
+                    "cannot find the ~> of ~<",
+                ));
+            }
+            if buf.len() > 1 {
+                cache.push(Tilde::new(
+                    buf.len() - 1,
+                    TildeKind::Text(
+                        String::from_utf8(buf[..buf.len() - 1].to_vec())
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?
+                            .into(),
+                    ),
+                ));
+            }
+            buf.clear();
+
+            // the separator or the end may have parameters and modifiers
+            let tilde_start = c.position() - 1;
+            let mut one_byte = [0u8; 1];
+            loop {
+                c.read_exact(&mut one_byte)
+                    .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+                if !matches!(one_byte[0], b'0'..=b'9' | b',' | b':' | b'@') {
+                    break;
+                }
+                buf.push(one_byte[0]);
+            }
+
+            match one_byte[0] {
+                b';' | b'>' => {
+                    let cache_len = cache.iter().map(|t: &Tilde| t.len()).sum::<usize>();
+                    segments.push(Tilde::new(
+                        cache_len,
+                        TildeKind::VecTilde(cache.clone().into()),
+                    ));
+                    cache.clear();
+
+                    if one_byte[0] == b'>' {
+                        break;
+                    }
+
+                    let (params, colon, _) = split_params(&String::from_utf8_lossy(&buf), 2)?;
+                    if colon {
+                        if segments.len() != 1 {
+                            return Err(TildeError::new(
+                                ErrorKind::ParseError,
+                                "~:; can only be the first separator of ~<",
+                            ));
+                        }
+                        overflow = Some((
+                            nth_param(&params, 0)?.unwrap_or(0),
+                            nth_param(&params, 1)?.unwrap_or(72),
+                        ));
+                    }
+                }
+                _ => {
+                    c.seek(SeekFrom::Start(tilde_start))
+                        .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+                    cache.push(Self::parse(c)?);
+                }
+            }
+            buf.clear();
+        }
+
+        Ok(Tilde::new(
+            (c.position() - start) as usize,
+            TildeKind::Justify((segments.into(), padding, colon, at, overflow)),
+        ))
+    }
+
+    /// parse the ~^ in loop
+    fn parse_loop_end(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let whole = Self::read_directive(c, b"^")?;
+        let (params, colon, _) = split_params(&whole[1..whole.len() - 1], 3)?;
+        Ok(Tilde::new(
+            whole.len(),
+            TildeKind::LoopEnd((
+                nth_param(&params, 0)?,
+                nth_param(&params, 1)?,
+                nth_param(&params, 2)?,
+                colon,
+            )),
+        ))
+    }
+
+    /// parse the '~[~]'
+    fn parse_cond(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let mut char_buf = [0u8; 3]; // three bytes
+        let mut total_len = 0;
+        let mut cond_kind;
+        c.read(&mut char_buf)
+            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+
+        match char_buf {
+            [b'~', b'[', ..] => {
+                total_len += 2;
+                cond_kind = TildeCondKind::Nil(false);
+                c.seek(SeekFrom::Current(-1))
+                    .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+            }
+            [b'~', b'#', b'['] => {
+                total_len += 3;
+                cond_kind = TildeCondKind::Sharp;
+            }
+            [b'~', b'@', b'['] => {
+                total_len += 3;
+                cond_kind = TildeCondKind::At;
+            }
+            [b'~', b':', b'['] => {
+                total_len += 3;
+                cond_kind = TildeCondKind::Colon;
+            }
+            _ => {
+                c.seek(SeekFrom::Current(-3))
+                    .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+                return Err(TildeError::new(
+                    ErrorKind::ParseError,
+                    "should start with ~[, ~#[, ~@[",
+                ));
+            }
+        }
+
+        let mut buffer = vec![];
+        let mut one_byte = [0_u8; 1];
+
+        let mut cache = vec![];
+        let mut result: Vec<Tilde> = vec![];
+
+        loop {
+            c.read_exact(&mut one_byte)
+                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+            if one_byte == [0] {
+                break;
+            }
+
+            if one_byte == [b'~'] {
+                if !buffer.is_empty() {
+                    cache.push(Tilde::new(
+                        buffer.len(),
+                        TildeKind::Text(
+                            String::from_utf8(buffer.clone())
+                                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?
+                                .into(),
+                        ),
+                    ));
+                    buffer.clear();
+                }
+
+                one_byte[0] = 0;
+                c.read_exact(&mut one_byte)
+                    .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+                match one_byte {
+                    [b':'] => {
+                        one_byte[0] = 0;
+                        c.read_exact(&mut one_byte)
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+                        if one_byte == [b';'] {
+                            let cache_len = cache.iter().map(|t: &Tilde| t.len()).sum::<usize>();
+                            result.push(Tilde::new(
+                                cache_len,
+                                TildeKind::VecTilde(cache.clone().into()),
+                            ));
+                            cond_kind.to_true();
+                            cache.clear();
+                            total_len += 3 + cache_len;
+                        } else {
+                            // other ~: tildes, like ~:^ and ~:d, are parsed as usual
+                            c.seek(SeekFrom::Current(-3)).map_err(|e| {
+                                TildeError::new(ErrorKind::ParseError, e.to_string())
+                            })?;
+                            let c = Self::parse(c)?;
+                            cache.push(c);
+                        }
+                    }
+                    [b';'] => {
+                        let cache_len = cache.iter().map(|t: &Tilde| t.len()).sum::<usize>();
+                        result.push(Tilde::new(
+                            cache_len,
+                            TildeKind::VecTilde(cache.clone().into()),
+                        ));
+                        cache.clear();
+                        total_len += 2 + cache_len;
+                    }
+                    [b']'] => {
+                        let cache_len = cache.iter().map(|t: &Tilde| t.len()).sum::<usize>();
+                        result.push(Tilde::new(
+                            cache_len,
+                            TildeKind::VecTilde(cache.clone().into()),
+                        ));
+                        total_len += 2 + cache_len;
+                        break;
+                    }
+                    _ => {
+                        c.seek(SeekFrom::Current(-2))
+                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+                        let c = Self::parse(c)?;
+                        cache.push(c);
+                    }
+                }
+                one_byte[0] = 0;
+            } else {
+                buffer.push(one_byte[0]);
+            }
+        }
+
+        Ok(Tilde::new(
+            total_len,
+            TildeKind::Cond((result.into(), cond_kind)),
+        ))
+    }
+
+    #[cfg(test)]
+    fn parse_vec(
+        c: &mut Cursor<&'_ str>,
+        end_len: usize,
+    ) -> Result<Self, Box<dyn std::error::Error>> {
+        let mut bucket = vec![0; end_len].into_boxed_slice();
+        c.read_exact(&mut bucket)?;
+
+        let ss = String::from_utf8(bucket.as_ref().to_vec())?;
+        //dbg!(&ss);
+        let mut inner_c = Cursor::new(ss.as_str());
+        let mut buf = vec![];
+        let mut result = vec![];
+        let mut total_len = 0;
+
+        loop {
+            // read text until the next '~'
+            inner_c.read_until(b'~', &mut buf)?;
+
+            match buf.as_slice() {
+                [b'~'] => {
+                    inner_c.seek(SeekFrom::Current(-1))?;
+                }
+                [.., b'~'] => {
+                    inner_c.seek(SeekFrom::Current(-1))?;
+                    result.push(Tilde::new(
+                        buf.len() - 1,
+                        TildeKind::Text(String::from_utf8(buf[..buf.len() - 1].to_vec())?.into()),
+                    ));
+                    total_len += buf.len() - 1;
+                }
+                [] => {
+                    c.seek(SeekFrom::Current((end_len - total_len) as i64 * -1))?;
+                    return Ok(Tilde::new(total_len, TildeKind::VecTilde(result.into())));
+                }
+                [..] => {
+                    result.push(Tilde::new(
+                        buf.len(),
+                        TildeKind::Text(String::from_utf8(buf[..buf.len()].to_vec())?.into()),
+                    ));
+                    total_len += buf.len();
+                    c.seek(SeekFrom::Current((end_len - total_len) as i64 * -1))?;
+                    return Ok(Tilde::new(total_len, TildeKind::VecTilde(result.into())));
+                }
+            }
+
+            let next = Tilde::parse(&mut inner_c)?;
+            total_len += next.len;
+            result.push(next);
+
+            buf.clear()
+        }
+    }
+
+    /// parse function for '~a'
+    fn parse_value(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let whole = Self::read_directive(c, b"aA")?;
+        Ok(Tilde::new(
+            whole.len(),
+            TildeKind::Va(parse_padding(&whole[1..whole.len() - 1])?),
+        ))
+    }
+
+    /// parse the float, `~w,d,k,overflowchar,padcharF`
+    fn parse_float(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let whole = Self::read_directive(c, b"fF")?;
+        let (params, _, at) = split_params(&whole[1..whole.len() - 1], 5)?;
+
+        Ok(Tilde::new(
+            whole.len(),
+            TildeKind::Float((
+                nth_param(&params, 0)?,
+                nth_param(&params, 1)?,
+                nth_param(&params, 2)?,
+                nth_char_param(&params, 3),
+                nth_char_param(&params, 4),
+                at,
+            )),
+        ))
+    }
+
+    /// parse the exponential float, `~w,d,e,k,overflowchar,padchar,exptcharE`
+    fn parse_exponential(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let whole = Self::read_directive(c, b"eE")?;
+        let (params, _, at) = split_params(&whole[1..whole.len() - 1], 7)?;
+
+        Ok(Tilde::new(
+            whole.len(),
+            TildeKind::Exponential((
+                nth_param(&params, 0)?,
+                nth_param(&params, 1)?,
+                nth_param(&params, 2)?,
+                nth_param(&params, 3)?,
+                nth_char_param(&params, 4),
+                nth_char_param(&params, 5),
+                nth_char_param(&params, 6),
+                at,
+            )),
+        ))
+    }
+
+    /// parse the general float, `~w,d,e,k,overflowchar,padchar,exptcharG`
+    fn parse_general(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let whole = Self::read_directive(c, b"gG")?;
+        let (params, _, at) = split_params(&whole[1..whole.len() - 1], 7)?;
+
+        Ok(Tilde::new(
+            whole.len(),
+            TildeKind::General((
+                nth_param(&params, 0)?,
+                nth_param(&params, 1)?,
+                nth_param(&params, 2)?,
+                nth_param(&params, 3)?,
+                nth_char_param(&params, 4),
+                nth_char_param(&params, 5),
+                nth_char_param(&params, 6),
+                at,
+            )),
+        ))
+    }
+
+    /// parse the monetary, `~d,n,w,padchar$`
+    fn parse_monetary(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let whole = Self::read_directive(c, b"$")?;
+        let (params, colon, at) = split_params(&whole[1..whole.len() - 1], 4)?;
+
+        Ok(Tilde::new(
+            whole.len(),
+            TildeKind::Monetary((
+                nth_param(&params, 0)?,
+                nth_param(&params, 1)?,
+                nth_param(&params, 2)?,
+                nth_char_param(&params, 3),
+                colon,
+                at,
+            )),
+        ))
+    }
+
+    /// parse the digit, `~mincol,padchar,commachar,comma-intervalD`
+    fn parse_digit(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let (len, params) = Self::read_integer_params(c, b"dD")?;
+        Ok(Tilde::new(len, TildeKind::Digit(params)))
+    }
+
+    /// parse the binary, `~mincol,padchar,commachar,comma-intervalB`
+    fn parse_binary(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let (len, params) = Self::read_integer_params(c, b"bB")?;
+        Ok(Tilde::new(len, TildeKind::Binary(params)))
+    }
+
+    /// parse the octal, `~mincol,padchar,commachar,comma-intervalO`
+    fn parse_octal(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let (len, params) = Self::read_integer_params(c, b"oO")?;
+        Ok(Tilde::new(len, TildeKind::Octal(params)))
+    }
+
+    /// parse the hexadecimal, `~mincol,padchar,commachar,comma-intervalX`
+    fn parse_hex(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let (len, params) = Self::read_integer_params(c, b"xX")?;
+        Ok(Tilde::new(len, TildeKind::Hex(params)))
+    }
+
+    /// read the parameters shared by `~D`, `~B`, `~O`, and `~X`, return the length and the parameters
+    #[allow(clippy::type_complexity)] // the parameters of the TildeKind variants
+    fn read_integer_params(
+        c: &mut Cursor<&'_ str>,
+        ends: &[u8],
+    ) -> Result<
+        (
+            usize,
+            (
+                Option<usize>,
+                Option<char>,
+                Option<char>,
+                Option<usize>,
+                Option<RadixFlag>,
+            ),
+        ),
+        TildeError,
+    > {
+        let whole = Self::read_directive(c, ends)?;
+        let (params, colon, at) = split_params(&whole[1..whole.len() - 1], 4)?;
+
+        Ok((
+            whole.len(),
+            (
+                nth_param(&params, 0)?,
+                nth_char_param(&params, 1),
+                nth_char_param(&params, 2),
+                nth_param(&params, 3)?,
+                radix_flag(colon, at),
+            ),
+        ))
+    }
+
+    /// parse the `~n*`, `~n:*`, and `~n@*`
+    fn parse_star(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let whole = Self::read_directive(c, b"*")?;
+        let (params, colon, at) = split_params(&whole[1..whole.len() - 1], 1)?;
+        let n = nth_param(&params, 0)?;
+        let kind = match (colon, at) {
+            (false, false) => StarKind::Skip(n.unwrap_or(1)),
+            (true, false) => StarKind::Hop(n.unwrap_or(1)),
+            (false, true) => StarKind::Goto(n.unwrap_or(0)),
+            (true, true) => {
+                return Err(TildeError::new(
+                    ErrorKind::ParseError,
+                    "~:@* is not a valid directive",
+                ))
+            }
+        };
+
+        Ok(Tilde::new(whole.len(), TildeKind::Star(kind)))
+    }
+
+    /// parse the `~` followed by a newline, it ignores the newline and the whitespaces after it.
+    /// `~:` keeps the whitespaces and `~@` keeps the newline, so it is just a text after parsing
+    fn parse_tilde_newline(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let whole = Self::read_directive(c, b"\n")?;
+        let (_, colon, at) = split_params(&whole[1..whole.len() - 1], 0)?;
+
+        let rest = &c.get_ref()[c.position() as usize..];
+        let ws = &rest[..rest.len()
+            - rest
+                .trim_start_matches(|ch: char| ch != '\n' && ch.is_whitespace())
+                .len()];
+        c.seek(SeekFrom::Current(ws.len() as i64))
+            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+
+        let mut text = String::new();
+        if at {
+            text.push('\n');
+        }
+        if colon {
+            text.push_str(ws);
+        }
+
+        Ok(Tilde::new(
+            whole.len() + ws.len(),
+            TildeKind::Text(text.into()),
+        ))
+    }
+
+    /// parse the `~n~`
+    fn parse_tildes(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let (len, n) = Self::read_count(c, b"~")?;
+        Ok(Tilde::new(len, TildeKind::Tildes(n)))
+    }
+
+    /// parse the new line `~n%`
+    fn parse_newline(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let (len, n) = Self::read_count(c, b"%")?;
+        Ok(Tilde::new(len, TildeKind::Newline(n)))
+    }
+
+    /// parse the fresh line `~n&`
+    fn parse_fresh_line(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let (len, n) = Self::read_count(c, b"&")?;
+        Ok(Tilde::new(len, TildeKind::FreshLine(n)))
+    }
+
+    /// parse the page separator `~n|`
+    fn parse_page(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let (len, n) = Self::read_count(c, b"|")?;
+        Ok(Tilde::new(len, TildeKind::Page(n)))
+    }
+
+    /// read the directive only has the repeat count `n` (default 1), return the length and the count
+    fn read_count(c: &mut Cursor<&'_ str>, ends: &[u8]) -> Result<(usize, usize), TildeError> {
+        let whole = Self::read_directive(c, ends)?;
+        let (params, _, _) = split_params(&whole[1..whole.len() - 1], 1)?;
+        Ok((whole.len(), nth_param(&params, 0)?.unwrap_or(1)))
+    }
+
+    fn parse_standard(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let whole = Self::read_directive(c, b"sS")?;
+        Ok(Tilde::new(
+            whole.len(),
+            TildeKind::Standard(parse_padding(&whole[1..whole.len() - 1])?),
+        ))
+    }
+
+    fn parse_char(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let whole = Self::read_directive(c, b"cC")?;
+        let (_, colon, at) = split_params(&whole[1..whole.len() - 1], 0)?;
+        let kind = match (colon, at) {
+            (false, false) => CharKind::Nil,
+            (false, true) => CharKind::At,
+            (true, false) => CharKind::Colon,
+            (true, true) => CharKind::AtColon,
+        };
+
+        Ok(Tilde::new(whole.len(), TildeKind::Char(kind)))
+    }
+
+    /// parse the radix, `~radix,mincol,padchar,commachar,comma-intervalR`
+    fn parse_radix(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
+        let whole = Self::read_directive(c, b"rR")?;
+        let (params, colon, at) = split_params(&whole[1..whole.len() - 1], 5)?;
+
+        Ok(Tilde::new(
+            whole.len(),
+            TildeKind::Radix((
+                nth_param(&params, 0)?,
+                nth_param(&params, 1)?,
+                nth_char_param(&params, 2),
+                nth_char_param(&params, 3),
+                nth_param(&params, 4)?,
+                radix_flag(colon, at),
+            )),
+        ))
+    }
+
+    /// read from '~' to the first unescaped char inside `ends`, return the whole directive
+    fn read_directive(c: &mut Cursor<&'_ str>, ends: &[u8]) -> Result<String, TildeError> {
+        let mut buf = [0u8; 1];
+        let mut bucket = vec![];
+        let mut escape = false;
+        loop {
+            if c.read(&mut buf)
+                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?
+                == 0
+                || buf[0] == 0_u8
+            {
+                return Err(TildeError::new(ErrorKind::ParseError, "read to end"));
+            }
+
+            bucket.extend_from_slice(&buf);
+            if escape {
+                escape = false;
+                continue;
+            }
+
+            match buf[0] {
+                b'\\' | b'\'' => escape = true,
+                b if bucket.len() > 1 && ends.contains(&b) => break,
+                _ => (),
+            }
+        }
+
+        String::from_utf8(bucket).map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))
+    }
+
+    // more parsers functions below
+}
+
+/// split the parameters and the ':' and '@' modifiers of one directive.
+///
+/// `s` is the part between the '~' and the directive char, like `6,2,,'*:@`.
+/// The char after '\'' or '\\' is always the part of the parameter.
+fn split_params(s: &str, max: usize) -> Result<(Vec<String>, bool, bool), TildeError> {
+    let mut params = vec![String::new()];
+    let mut colon = false;
+    let mut at = false;
+    let mut escape = false;
+
+    for ch in s.chars() {
+        if escape {
+            params.last_mut().unwrap().push(ch);
+            escape = false;
+            continue;
+        }
+
+        match ch {
+            '\\' | '\'' => {
+                escape = true;
+                params.last_mut().unwrap().push(ch);
+            }
+            ',' => params.push(String::new()),
+            ':' => colon = true,
+            '@' => at = true,
+            _ => params.last_mut().unwrap().push(ch),
+        }
+    }
+
+    if params.len() == 1 && params[0].is_empty() {
+        // no parameter at all
+        params.clear();
+    }
+
+    if params.len() > max {
+        return Err(TildeError::new(
+            ErrorKind::ParseError,
+            format!("too many parameters: {}, at most {}", s, max),
+        ));
+    }
+
+    Ok((params, colon, at))
+}
+
+/// parse the n-th parameter, the empty or missing parameter is None
+fn nth_param<T>(params: &[String], n: usize) -> Result<Option<T>, TildeError>
+where
+    T: std::str::FromStr,
+    T::Err: std::fmt::Display,
+{
+    match params.get(n) {
+        Some(p) if !p.is_empty() => Ok(Some(p.parse::<T>().map_err(|e| {
+            TildeError::new(ErrorKind::ParseError, format!("parameter {}: {}", p, e))
+        })?)),
+        _ => Ok(None),
+    }
+}
+
+/// the n-th char parameter is the last char of it, so `'0`, `\a`, and `0` are all fine
+fn nth_char_param(params: &[String], n: usize) -> Option<char> {
+    params.get(n).and_then(|p| p.chars().last())
+}
+
+/// parse the `mincol,colinc,minpad,padchar` and '@' of `~A` and `~S`, None if no padding
+#[allow(clippy::type_complexity)] // the parameters of the TildeKind variants
+fn parse_padding(s: &str) -> Result<Option<(usize, usize, usize, char, bool)>, TildeError> {
+    let (params, _, at) = split_params(s, 4)?;
+    if !at && params.iter().all(|p| p.is_empty()) {
+        return Ok(None);
+    }
+
+    Ok(Some((
+        nth_param(&params, 0)?.unwrap_or(0),
+        nth_param(&params, 1)?.unwrap_or(1),
+        nth_param(&params, 2)?.unwrap_or(0),
+        nth_char_param(&params, 3).unwrap_or(' '),
+        at,
+    )))
+}
+
+/// the ':' and '@' modifiers to the RadixFlag
+fn radix_flag(colon: bool, at: bool) -> Option<RadixFlag> {
+    match (colon, at) {
+        (true, true) => Some(RadixFlag::AtColon),
+        (true, false) => Some(RadixFlag::Colon),
+        (false, true) => Some(RadixFlag::At),
+        (false, false) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_cursor() {
+        let mut testcase0 = Cursor::new("abcd");
+        assert_eq!(testcase0.position(), 0);
+
+        let mut buffer: [u8; 1] = [0; 1];
+        testcase0.set_position(2);
+        let _ = testcase0.read(&mut buffer);
+        assert_eq!(buffer[0], b'c');
+    }
+
+    #[test]
+    fn test_parse_va() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~a");
+        assert_eq!(
+            Tilde::parse_value(&mut case)?,
+            Tilde::new(2, TildeKind::Va(None))
+        );
+
+        let mut case = Cursor::new("~A");
+        assert_eq!(
+            Tilde::parse_value(&mut case)?,
+            Tilde::new(2, TildeKind::Va(None))
+        );
+
+        let mut case = Cursor::new("~10,2,1,'*@a");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(12, TildeKind::Va(Some((10, 2, 1, '*', true))))
+        );
+
+        let mut case = Cursor::new("~5A");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(3, TildeKind::Va(Some((5, 1, 0, ' ', false))))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_loop() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~{~}");
+
+        assert_eq!(
+            Tilde::parse_loop(&mut case)?,
+            Tilde::new(
+                4,
+                TildeKind::Loop((Vec::new().into(), TildeLoopKind::Nil, None))
+            )
+        );
+
+        let mut case = Cursor::new("~{a bc~}");
+
+        assert_eq!(
+            Tilde::parse_loop(&mut case)?,
+            Tilde::new(
+                8,
+                TildeKind::Loop((
+                    vec![Tilde::new(4, TildeKind::Text(String::from("a bc").into()))].into(),
+                    TildeLoopKind::Nil,
+                    None
+                ))
+            ),
+        );
+
+        let mut case = Cursor::new("~{a bc~a~}");
+
+        assert_eq!(
+            Tilde::parse_loop(&mut case)?,
+            Tilde::new(
+                10,
+                TildeKind::Loop((
+                    vec![
+                        Tilde::new(4, TildeKind::Text(String::from("a bc").into())),
+                        Tilde::new(2, TildeKind::Va(None))
+                    ]
+                    .into(),
+                    TildeLoopKind::Nil,
+                    None
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~{~aa bc~a~}");
+
+        assert_eq!(
+            Tilde::parse_loop(&mut case)?,
+            Tilde::new(
+                12,
+                TildeKind::Loop((
+                    vec![
+                        Tilde::new(2, TildeKind::Va(None)),
+                        Tilde::new(4, TildeKind::Text(String::from("a bc").into())),
+                        Tilde::new(2, TildeKind::Va(None))
+                    ]
+                    .into(),
+                    TildeLoopKind::Nil,
+                    None
+                ))
+            )
+        );
+
+        // cannot find the ~}
+        assert!(Tilde::parse_loop(&mut Cursor::new("~{~a")).is_err());
+        assert!(Tilde::parse_loop(&mut Cursor::new("~{abc")).is_err());
+
+        let mut case = Cursor::new("~5{~a~}");
+
+        assert_eq!(
+            Tilde::parse_loop(&mut case)?,
+            Tilde::new(
+                7,
+                TildeKind::Loop((
+                    vec![Tilde::new(2, TildeKind::Va(None))].into(),
+                    TildeLoopKind::Nil,
+                    Some(5)
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~@{~a~}");
+
+        assert_eq!(
+            Tilde::parse_loop(&mut case)?,
+            Tilde::new(
+                7,
+                TildeKind::Loop((
+                    vec![Tilde::new(2, TildeKind::Va(None)),].into(),
+                    TildeLoopKind::At,
+                    None
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~@{~a~^, ~}");
+
+        assert_eq!(
+            Tilde::parse_loop(&mut case)?,
+            Tilde::new(
+                11,
+                TildeKind::Loop((
+                    vec![
+                        Tilde::new(2, TildeKind::Va(None)),
+                        Tilde::new(2, TildeKind::LoopEnd((None, None, None, false))),
+                        Tilde::new(2, TildeKind::Text(", ".to_string().into()))
+                    ]
+                    .into(),
+                    TildeLoopKind::At,
+                    None
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~{~a~:}");
+        assert_eq!(
+            Tilde::parse_loop(&mut case)?,
+            Tilde::new(
+                7,
+                TildeKind::Loop((
+                    vec![Tilde::new(2, TildeKind::Va(None)),].into(),
+                    TildeLoopKind::NilColon,
+                    None
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~:{~a~:^, ~}");
+        assert_eq!(
+            Tilde::parse_loop(&mut case)?,
+            Tilde::new(
+                12,
+                TildeKind::Loop((
+                    vec![
+                        Tilde::new(2, TildeKind::Va(None)),
+                        Tilde::new(3, TildeKind::LoopEnd((None, None, None, true))),
+                        Tilde::new(2, TildeKind::Text(", ".to_string().into())),
+                    ]
+                    .into(),
+                    TildeLoopKind::Colon,
+                    None
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~:@{~a~}");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                8,
+                TildeKind::Loop((
+                    vec![Tilde::new(2, TildeKind::Va(None))].into(),
+                    TildeLoopKind::AtColon,
+                    None
+                ))
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_float() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~f");
+        assert_eq!(
+            Tilde::parse_float(&mut case)?,
+            Tilde::new(2, TildeKind::Float((None, None, None, None, None, false)))
+        );
+
+        let mut case = Cursor::new("~,5f");
+        assert_eq!(
+            Tilde::parse_float(&mut case)?,
+            Tilde::new(
+                4,
+                TildeKind::Float((None, Some(5), None, None, None, false))
+            )
+        );
+
+        let mut case = Cursor::new("~6,2,-1,'*,'0@F rest");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                15,
+                TildeKind::Float((Some(6), Some(2), Some(-1), Some('*'), Some('0'), true))
+            )
+        );
+
+        // 'f' inside the char parameter
+        let mut case = Cursor::new("~6,2,,'fF");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                9,
+                TildeKind::Float((Some(6), Some(2), None, Some('f'), None, false))
+            )
+        );
+
+        let mut case = Cursor::new("~1,2,3,4,5,6F");
+        assert!(Tilde::parse(&mut case).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_exponential() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~e");
+        assert_eq!(
+            Tilde::parse_exponential(&mut case)?,
+            Tilde::new(
+                2,
+                TildeKind::Exponential((None, None, None, None, None, None, None, false))
+            )
+        );
+
+        let mut case = Cursor::new("~10,3,2,2,'?,,'$E");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                17,
+                TildeKind::Exponential((
+                    Some(10),
+                    Some(3),
+                    Some(2),
+                    Some(2),
+                    Some('?'),
+                    None,
+                    Some('$'),
+                    false
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~9,3,2,-2,'%@E");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                14,
+                TildeKind::Exponential((
+                    Some(9),
+                    Some(3),
+                    Some(2),
+                    Some(-2),
+                    Some('%'),
+                    None,
+                    None,
+                    true
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~1,2,3,4,5,6,7,8E");
+        assert!(Tilde::parse(&mut case).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_general() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~G");
+        assert_eq!(
+            Tilde::parse_general(&mut case)?,
+            Tilde::new(
+                2,
+                TildeKind::General((None, None, None, None, None, None, None, false))
+            )
+        );
+
+        let mut case = Cursor::new("~9,3,2,3,'?,,'$g");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                16,
+                TildeKind::General((
+                    Some(9),
+                    Some(3),
+                    Some(2),
+                    Some(3),
+                    Some('?'),
+                    None,
+                    Some('$'),
+                    false
+                ))
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_digit() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~d");
+        assert_eq!(
+            Tilde::parse_digit(&mut case)?,
+            Tilde::new(2, TildeKind::Digit((None, None, None, None, None)))
+        );
+
+        let mut case = Cursor::new("~:D");
+        assert_eq!(
+            Tilde::parse_digit(&mut case)?,
+            Tilde::new(
+                3,
+                TildeKind::Digit((None, None, None, None, Some(RadixFlag::Colon)))
+            )
+        );
+
+        let mut case = Cursor::new("~10,'0,'d,4:@d");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                14,
+                TildeKind::Digit((
+                    Some(10),
+                    Some('0'),
+                    Some('d'),
+                    Some(4),
+                    Some(RadixFlag::AtColon)
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~1,2,3,4,5d");
+        assert!(Tilde::parse(&mut case).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_binary_octal_hex() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~8,'0X");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(6, TildeKind::Hex((Some(8), Some('0'), None, None, None)))
+        );
+
+        let mut case = Cursor::new("~,,' ,4:B");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                9,
+                TildeKind::Binary((None, None, Some(' '), Some(4), Some(RadixFlag::Colon)))
+            )
+        );
+
+        let mut case = Cursor::new("~@o");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                3,
+                TildeKind::Octal((None, None, None, None, Some(RadixFlag::At)))
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_case_conv() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~(hello ~a~) rest");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                12,
+                TildeKind::CaseConv((
+                    vec![
+                        Tilde::new(6, TildeKind::Text("hello ".to_string().into())),
+                        Tilde::new(2, TildeKind::Va(None))
+                    ]
+                    .into(),
+                    CaseConvKind::Lower
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~:@(~{~a~}~)");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                12,
+                TildeKind::CaseConv((
+                    vec![Tilde::new(
+                        6,
+                        TildeKind::Loop((
+                            vec![Tilde::new(2, TildeKind::Va(None))].into(),
+                            TildeLoopKind::Nil,
+                            None
+                        ))
+                    )]
+                    .into(),
+                    CaseConvKind::Upper
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~:(a~@(b~)~)");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                12,
+                TildeKind::CaseConv((
+                    vec![
+                        Tilde::new(1, TildeKind::Text("a".to_string().into())),
+                        Tilde::new(
+                            6,
+                            TildeKind::CaseConv((
+                                vec![Tilde::new(1, TildeKind::Text("b".to_string().into()))].into(),
+                                CaseConvKind::CapitalizeFirst
+                            ))
+                        )
+                    ]
+                    .into(),
+                    CaseConvKind::Capitalize
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~(never closed");
+        assert!(Tilde::parse(&mut case).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_tabulate() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~T");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(2, TildeKind::Tabulate((None, None, false)))
+        );
+
+        let mut case = Cursor::new("~20,8t");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(6, TildeKind::Tabulate((Some(20), Some(8), false)))
+        );
+
+        let mut case = Cursor::new("~3@T");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(4, TildeKind::Tabulate((Some(3), None, true)))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_justify() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~10<foo~;bar~>");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                14,
+                TildeKind::Justify((
+                    vec![
+                        Tilde::new(
+                            3,
+                            TildeKind::VecTilde(
+                                vec![Tilde::new(3, TildeKind::Text("foo".to_string().into()))]
+                                    .into()
+                            )
+                        ),
+                        Tilde::new(
+                            3,
+                            TildeKind::VecTilde(
+                                vec![Tilde::new(3, TildeKind::Text("bar".to_string().into()))]
+                                    .into()
+                            )
+                        )
+                    ]
+                    .into(),
+                    (10, 1, 0, ' '),
+                    false,
+                    false,
+                    None
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~:@<~%~2,40:;~a~>");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                17,
+                TildeKind::Justify((
+                    vec![
+                        Tilde::new(
+                            2,
+                            TildeKind::VecTilde(vec![Tilde::new(2, TildeKind::Newline(1))].into())
+                        ),
+                        Tilde::new(
+                            2,
+                            TildeKind::VecTilde(vec![Tilde::new(2, TildeKind::Va(None))].into())
+                        )
+                    ]
+                    .into(),
+                    (0, 1, 0, ' '),
+                    true,
+                    true,
+                    Some((2, 40))
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~<a~;b~:;c~>");
+        assert!(Tilde::parse(&mut case).is_err());
+
+        let mut case = Cursor::new("~<never closed");
+        assert!(Tilde::parse(&mut case).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_loop_end() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~^");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(2, TildeKind::LoopEnd((None, None, None, false)))
+        );
+
+        let mut case = Cursor::new("~:^");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(3, TildeKind::LoopEnd((None, None, None, true)))
+        );
+
+        let mut case = Cursor::new("~1,-2,3^ rest");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(8, TildeKind::LoopEnd((Some(1), Some(-2), Some(3), false)))
+        );
+
+        let mut case = Cursor::new("~1,2,3,4^");
+        assert!(Tilde::parse(&mut case).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_params() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~v,'0d rest");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::with_params(
+                6,
+                TildeKind::Digit((None, Some('0'), None, None, None)),
+                vec![(0, TildeParam::Arg)].into()
+            )
+        );
+        assert_eq!(case.position(), 6);
+
+        let mut case = Cursor::new("~#,V:@<~a~;~a~>");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::with_params(
+                15,
+                TildeKind::Justify((
+                    vec![
+                        Tilde::new(
+                            2,
+                            TildeKind::VecTilde(vec![Tilde::new(2, TildeKind::Va(None))].into())
+                        ),
+                        Tilde::new(
+                            2,
+                            TildeKind::VecTilde(vec![Tilde::new(2, TildeKind::Va(None))].into())
+                        ),
+                    ]
+                    .into(),
+                    (0, 1, 0, ' '),
+                    true,
+                    true,
+                    None
+                )),
+                vec![(0, TildeParam::ArgsCount), (1, TildeParam::Arg)].into()
+            )
+        );
+
+        // the omitted trailing parameters of ~R
+        let mut case = Cursor::new("~v,8,'0R");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::with_params(
+                8,
+                TildeKind::Radix((None, Some(8), Some('0'), None, None, None)),
+                vec![(0, TildeParam::Arg)].into()
+            )
+        );
+
+        // ~#[ is still the condition
+        let mut case = Cursor::new("~#[~;a~]");
+        assert!(matches!(
+            Tilde::parse(&mut case)?.value,
+            TildeKind::Cond((_, TildeCondKind::Sharp))
+        ));
+
+        let mut case = Cursor::new("~v,1,2P");
+        assert!(Tilde::parse(&mut case).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_recursive() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~?");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(2, TildeKind::Recursive(false))
+        );
+
+        let mut case = Cursor::new("~@? rest");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(3, TildeKind::Recursive(true))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_plural() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~p");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(2, TildeKind::Plural((false, false)))
+        );
+
+        let mut case = Cursor::new("~:@P rest");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(4, TildeKind::Plural((true, true)))
+        );
+
+        let mut case = Cursor::new("~1P");
+        assert!(Tilde::parse(&mut case).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_monetary() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~$");
+        assert_eq!(
+            Tilde::parse_monetary(&mut case)?,
+            Tilde::new(
+                2,
+                TildeKind::Monetary((None, None, None, None, false, false))
+            )
+        );
+
+        let mut case = Cursor::new("~5$");
+        assert_eq!(
+            Tilde::parse_monetary(&mut case)?,
+            Tilde::new(
+                3,
+                TildeKind::Monetary((Some(5), None, None, None, false, false))
+            )
+        );
+
+        let mut case = Cursor::new("~2,4,10,'*:@$");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                13,
+                TildeKind::Monetary((Some(2), Some(4), Some(10), Some('*'), true, true))
+            )
+        );
+
+        let mut case = Cursor::new("~1,2,3,4,5$");
+        assert!(Tilde::parse(&mut case).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_scan_for_kind() -> Result<(), Box<dyn std::error::Error>> {
+        let case = "~a";
+        let mut c = Cursor::new(case);
+        let f = Tilde::scan_for_kind(&mut c)?;
+
+        // let mut ss = String::new();
+        // c.read_to_string(&mut ss);
+        // dbg!(ss);
+        // c.seek(SeekFrom::Start(0));
+        //dbg!(f(&mut c));
+
+        assert_eq!(Tilde::new(2, TildeKind::Va(None)), f(&mut c)?);
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_vec() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~a and ~a~a~a");
+        assert_eq!(
+            Tilde::parse_vec(&mut case, 9)?,
+            Tilde::new(
+                9,
+                TildeKind::VecTilde(
+                    vec![
+                        Tilde::new(2, TildeKind::Va(None)),
+                        Tilde::new(5, TildeKind::Text(String::from(" and ").into())),
+                        Tilde::new(2, TildeKind::Va(None))
+                    ]
+                    .into()
+                )
+            )
+        );
+
+        //dbg!(&case.position());
+        let mut rest = vec![];
+        case.read_to_end(&mut rest)?;
+        assert_eq!(String::from_utf8(rest)?, "~a~a");
+
+        //
+        let mut case = Cursor::new("~a a");
+        assert!(Tilde::parse_vec(&mut case, 9).is_err());
+
+        //
+        let mut case = Cursor::new("a");
+        assert_eq!(
+            Tilde::parse_vec(&mut case, 1)?,
+            Tilde::new(
+                1,
+                TildeKind::VecTilde(
+                    vec![Tilde::new(1, TildeKind::Text(String::from("a").into()))].into()
+                )
+            )
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_cond() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~[~]");
+
+        assert_eq!(
+            Tilde::parse_cond(&mut case)?,
+            Tilde::new(
+                4,
+                TildeKind::Cond((
+                    vec![Tilde::new(0, TildeKind::VecTilde(vec![].into()))].into(),
+                    TildeCondKind::Nil(false)
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~[cero~]");
+
+        assert_eq!(
+            Tilde::parse_cond(&mut case)?,
+            Tilde::new(
+                8,
+                TildeKind::Cond((
+                    vec![Tilde::new(
+                        4,
+                        TildeKind::VecTilde(
+                            vec![Tilde::new(4, TildeKind::Text(String::from("cero").into()))]
+                                .into()
+                        )
+                    )]
+                    .into(),
+                    TildeCondKind::Nil(false)
+                ))
+            ),
+        );
+
+        let mut case = Cursor::new("~[cero~;uno~;dos~]");
+
+        assert_eq!(
+            Tilde::parse_cond(&mut case)?,
+            Tilde::new(
+                18,
+                TildeKind::Cond((
+                    vec![
+                        Tilde::new(
+                            4,
+                            TildeKind::VecTilde(
+                                vec![Tilde::new(4, TildeKind::Text(String::from("cero").into()))]
+                                    .into()
+                            )
+                        ),
+                        Tilde::new(
+                            3,
+                            TildeKind::VecTilde(
+                                vec![Tilde::new(3, TildeKind::Text(String::from("uno").into()))]
+                                    .into()
+                            )
+                        ),
+                        Tilde::new(
+                            3,
+                            TildeKind::VecTilde(
+                                vec![Tilde::new(3, TildeKind::Text(String::from("dos").into()))]
+                                    .into()
+                            )
+                        ),
+                    ]
+                    .into(),
+                    TildeCondKind::Nil(false)
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~[cero~;uno~;~]");
+
+        assert_eq!(
+            Tilde::parse_cond(&mut case)?,
+            Tilde::new(
+                15,
+                TildeKind::Cond((
+                    vec![
+                        Tilde::new(
+                            4,
+                            TildeKind::VecTilde(
+                                vec![Tilde::new(4, TildeKind::Text(String::from("cero").into()))]
+                                    .into()
+                            )
+                        ),
+                        Tilde::new(
+                            3,
+                            TildeKind::VecTilde(
+                                vec![Tilde::new(3, TildeKind::Text(String::from("uno").into()))]
+                                    .into()
+                            )
+                        ),
+                        Tilde::new(0, TildeKind::VecTilde(vec![].into())),
+                    ]
+                    .into(),
+                    TildeCondKind::Nil(false)
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~:[cero~;uno~]");
+        assert_eq!(
+            Tilde::parse_cond(&mut case)?,
+            Tilde::new(
+                14,
+                TildeKind::Cond((
+                    vec![
+                        Tilde::new(
+                            4,
+                            TildeKind::VecTilde(
+                                vec![Tilde::new(4, TildeKind::Text(String::from("cero").into()))]
+                                    .into()
+                            )
+                        ),
+                        Tilde::new(
+                            3,
+                            TildeKind::VecTilde(
+                                vec![Tilde::new(3, TildeKind::Text(String::from("uno").into()))]
+                                    .into()
+                            )
+                        ),
+                    ]
+                    .into(),
+                    TildeCondKind::Colon
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~[cero~;uno~:;dos~]");
+
+        assert_eq!(
+            Tilde::parse_cond(&mut case)?,
+            Tilde::new(
+                19,
+                TildeKind::Cond((
+                    vec![
+                        Tilde::new(
+                            4,
+                            TildeKind::VecTilde(
+                                vec![Tilde::new(4, TildeKind::Text(String::from("cero").into()))]
+                                    .into()
+                            )
+                        ),
+                        Tilde::new(
+                            3,
+                            TildeKind::VecTilde(
+                                vec![Tilde::new(3, TildeKind::Text(String::from("uno").into()))]
+                                    .into()
+                            )
+                        ),
+                        Tilde::new(
+                            3,
+                            TildeKind::VecTilde(
+                                vec![Tilde::new(3, TildeKind::Text(String::from("dos").into()))]
+                                    .into()
+                            )
+                        ),
+                    ]
+                    .into(),
+                    TildeCondKind::Nil(true)
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~#[NONE~;~a~;~a and ~a~:;~a, ~a~]");
+
+        assert_eq!(
+            Tilde::parse_cond(&mut case)?,
+            Tilde::new(
+                33,
+                TildeKind::Cond((
+                    vec![
+                        Tilde::new(
+                            4,
+                            TildeKind::VecTilde(
+                                vec![Tilde::new(4, TildeKind::Text(String::from("NONE").into()))]
+                                    .into()
+                            )
+                        ),
+                        Tilde::new(
+                            2,
+                            TildeKind::VecTilde(vec![Tilde::new(2, TildeKind::Va(None))].into())
+                        ),
+                        Tilde::new(
+                            9,
+                            TildeKind::VecTilde(
+                                vec![
+                                    Tilde::new(2, TildeKind::Va(None)),
+                                    Tilde::new(5, TildeKind::Text(String::from(" and ").into())),
+                                    Tilde::new(2, TildeKind::Va(None))
+                                ]
+                                .into()
+                            )
+                        ),
+                        Tilde::new(
+                            6,
+                            TildeKind::VecTilde(
+                                vec![
+                                    Tilde::new(2, TildeKind::Va(None)),
+                                    Tilde::new(2, TildeKind::Text(String::from(", ").into())),
+                                    Tilde::new(2, TildeKind::Va(None))
+                                ]
+                                .into()
+                            )
+                        ),
+                    ]
+                    .into(),
+                    TildeCondKind::Sharp
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~@[x = ~a ~]~@[y = ~a~]");
+        assert_eq!(
+            Tilde::parse_cond(&mut case)?,
+            Tilde::new(
+                12,
+                TildeKind::Cond((
+                    vec![Tilde::new(
+                        7,
+                        TildeKind::VecTilde(
+                            vec![
+                                Tilde::new(4, TildeKind::Text("x = ".into())),
+                                Tilde::new(2, TildeKind::Va(None)),
+                                Tilde::new(1, TildeKind::Text(" ".into()))
+                            ]
+                            .into()
+                        )
+                    )]
+                    .into(),
+                    TildeCondKind::At
+                ))
+            )
+        );
+
+        // parse the second part
+        assert_eq!(
+            Tilde::parse_cond(&mut case)?,
+            Tilde::new(
+                11,
+                TildeKind::Cond((
+                    vec![Tilde::new(
+                        6,
+                        TildeKind::VecTilde(
+                            vec![
+                                Tilde::new(4, TildeKind::Text("y = ".into())),
+                                Tilde::new(2, TildeKind::Va(None)),
+                            ]
+                            .into()
+                        )
+                    )]
+                    .into(),
+                    TildeCondKind::At
+                ))
+            )
+        );
+
+        // the ~: tildes other than ~:; inside the clauses
+        for (case, inner, cond_kind) in [
+            ("~[zero~;~:d~]", "~:d", TildeCondKind::Nil(false)),
+            ("~:[no~;~:d~]", "~:d", TildeCondKind::Colon),
+            ("~[~:(abc~)~]", "~:(abc~)", TildeCondKind::Nil(false)),
+            ("~@[~:C~]", "~:C", TildeCondKind::At),
+            ("~[~:^~;x~]", "~:^", TildeCondKind::Nil(false)),
+        ] {
+            let inner = Tilde::parse(&mut Cursor::new(inner))?;
+            let Tilde {
+                value: TildeKind::Cond((clauses, kind)),
+                len,
+                ..
+            } = Tilde::parse_cond(&mut Cursor::new(case))?
+            else {
+                panic!("{case} is not parsed to ~[")
+            };
+            assert_eq!(len, case.len());
+            assert_eq!(kind, cond_kind);
+            assert!(clauses
+                .iter()
+                .any(|c| matches!(c.value(), TildeKind::VecTilde(v) if v.contains(&inner))));
+        }
+
+        assert!(Tilde::parse_cond(&mut Cursor::new("~[~:")).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_star() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~:*");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(3, TildeKind::Star(StarKind::Hop(1)))
+        );
+
+        let mut case = Cursor::new("~*");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(2, TildeKind::Star(StarKind::Skip(1)))
+        );
+
+        let mut case = Cursor::new("~3:*");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(4, TildeKind::Star(StarKind::Hop(3)))
+        );
+
+        let mut case = Cursor::new("~@*");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(3, TildeKind::Star(StarKind::Goto(0)))
+        );
+
+        let mut case = Cursor::new("~12@* rest");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(5, TildeKind::Star(StarKind::Goto(12)))
+        );
+
+        let mut case = Cursor::new("~:@*");
+        assert!(Tilde::parse(&mut case).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_tildes() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~9~");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(3, TildeKind::Tildes(9))
+        );
+
+        let mut case = Cursor::new("~0~");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(3, TildeKind::Tildes(0))
+        );
+
+        let mut case = Cursor::new("~~");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(2, TildeKind::Tildes(1))
+        );
+
+        let mut case = Cursor::new("~12~a");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(4, TildeKind::Tildes(12))
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_newline_fresh_line_page() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~%");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(2, TildeKind::Newline(1))
+        );
+
+        let mut case = Cursor::new("~10%");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(4, TildeKind::Newline(10))
+        );
+
+        let mut case = Cursor::new("~&");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(2, TildeKind::FreshLine(1))
+        );
+
+        let mut case = Cursor::new("~0&");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(3, TildeKind::FreshLine(0))
+        );
+
+        let mut case = Cursor::new("~2|");
+        assert_eq!(Tilde::parse(&mut case)?, Tilde::new(3, TildeKind::Page(2)));
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_tilde_newline() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~\n   \tb");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(6, TildeKind::Text(String::new().into()))
+        );
+        assert_eq!(case.position(), 6);
+
+        let mut case = Cursor::new("~:\n  b");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(5, TildeKind::Text(String::from("  ").into()))
+        );
+
+        let mut case = Cursor::new("~@\n  b");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(5, TildeKind::Text(String::from("\n").into()))
+        );
+
+        // only the whitespaces on the next line
+        let mut case = Cursor::new("~\n\n b");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(2, TildeKind::Text(String::new().into()))
+        );
+
+        let mut case = Cursor::new("~\n");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(2, TildeKind::Text(String::new().into()))
+        );
+
+        assert!(Tilde::parse(&mut Cursor::new("~1\n")).is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_standard() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~s");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(2, TildeKind::Standard(None))
+        );
+
+        let mut case = Cursor::new("~S");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(2, TildeKind::Standard(None))
+        );
+
+        let mut case = Cursor::new("~S superbowl");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(2, TildeKind::Standard(None))
+        );
+
+        let mut case = Cursor::new("~8,,,'sS");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(8, TildeKind::Standard(Some((8, 1, 0, 's', false))))
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_char() -> Result<(), Box<dyn std::error::Error>> {
+        let mut case = Cursor::new("~c");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(2, TildeKind::Char(CharKind::Nil))
+        );
+
+        let mut case = Cursor::new("~@c");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(3, TildeKind::Char(CharKind::At))
+        );
+
+        let mut case = Cursor::new("~:C");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(3, TildeKind::Char(CharKind::Colon))
+        );
+
+        let mut case = Cursor::new("~@:c");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(4, TildeKind::Char(CharKind::AtColon))
+        );
+
+        assert!(Tilde::parse(&mut Cursor::new("~1c")).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_parse_radix() -> Result<(), Box<dyn std::error::Error>> {
+        //let mut case = Cursor::new("~2,8,'0,' ,R");
+        let mut case = Cursor::new("~2,8,0, ,R");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                10,
+                TildeKind::Radix((Some(2), Some(8), Some('0'), Some(' '), None, None))
+            )
+        );
+
+        let mut case = Cursor::new("~,,,,R");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(6, TildeKind::Radix((None, None, None, None, None, None)))
+        );
+
+        // the modifiers in either order
+        for case in ["~:@R", "~@:R"] {
+            assert_eq!(
+                Tilde::parse(&mut Cursor::new(case))?,
+                Tilde::new(
+                    4,
+                    TildeKind::Radix((None, None, None, None, None, Some(RadixFlag::AtColon)))
+                )
+            );
+        }
+
+        let mut case = Cursor::new("~2,8,0, ,4:R");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                12,
+                TildeKind::Radix((
+                    Some(2),
+                    Some(8),
+                    Some('0'),
+                    Some(' '),
+                    Some(4),
+                    Some(RadixFlag::Colon)
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~2,8,0, ,10:R");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                13,
+                TildeKind::Radix((
+                    Some(2),
+                    Some(8),
+                    Some('0'),
+                    Some(' '),
+                    Some(10),
+                    Some(RadixFlag::Colon)
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~3,,, ,2:R");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                10,
+                TildeKind::Radix((
+                    Some(3),
+                    None,
+                    None,
+                    Some(' '),
+                    Some(2),
+                    Some(RadixFlag::Colon)
+                ))
+            )
+        );
+
+        let mut case = Cursor::new("~2:R");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                4,
+                TildeKind::Radix((Some(2), None, None, None, None, Some(RadixFlag::Colon)))
+            )
+        );
+
+        let mut case = Cursor::new("~2R");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(3, TildeKind::Radix((Some(2), None, None, None, None, None)))
+        );
+
+        let mut case = Cursor::new("~R");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(2, TildeKind::Radix((None, None, None, None, None, None)))
+        );
+
+        let mut case = Cursor::new("~:R");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                3,
+                TildeKind::Radix((None, None, None, None, None, Some(RadixFlag::Colon)))
+            )
+        );
+
+        let mut case = Cursor::new("~@R");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                3,
+                TildeKind::Radix((None, None, None, None, None, Some(RadixFlag::At)))
+            )
+        );
+
+        let mut case = Cursor::new("~:@R");
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                4,
+                TildeKind::Radix((None, None, None, None, None, Some(RadixFlag::AtColon)))
+            )
+        );
+
+        let mut case = Cursor::new(r#"~2,,,\a,4:R"#);
+        assert_eq!(
+            Tilde::parse(&mut case)?,
+            Tilde::new(
+                11,
+                TildeKind::Radix((
+                    Some(2),
+                    None,
+                    None,
+                    Some('a'),
+                    Some(4),
+                    Some(RadixFlag::Colon)
+                ))
+            )
+        );
+
+        Ok(())
+    }
+}
@@ -0,0 +1,466 @@
+use super::Tilde;
+
+use std::borrow::Cow;
+use std::fmt::Debug;
+
+#[doc = "Error type for tildes parsing"]
+#[derive(Debug)]
+pub struct TildeError {
+    kind: ErrorKind,
+    msg: String,
+}
+
+impl TildeError {
+    pub fn new(kind: ErrorKind, msg: impl AsRef<str>) -> Self {
+        Self {
+            kind,
+            msg: msg.as_ref().to_string(),
+        }
+    }
+}
+
+impl TildeError {
+    /// the kind of this error
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+}
+
+impl std::error::Error for TildeError {}
+
+impl std::fmt::Display for TildeError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "TilderError {:?}: {}", self.kind, self.msg)
+    }
+}
+
+#[doc = "ErrorKind"]
+#[derive(Debug)]
+pub enum ErrorKind {
+    ParseError,
+    RevealError,
+    EmptyImplenmentError,
+    FormatError,
+    OutOfRange, // the arg cannot be revealed by the tilde, like ~@R of 4000
+}
+
+#[doc = "TildeCondKind"]
+#[derive(Debug, PartialEq, Clone)]
+pub enum TildeCondKind {
+    Nil(bool), // ~[, bool for the last ~:;
+    Sharp,     // ~#[
+    At,        // ~@[
+    Colon,     // ~:[
+}
+
+#[doc = "TildeLoopKind"]
+#[derive(Debug, PartialEq, Clone)]
+pub enum TildeLoopKind {
+    Nil,      // ~{~}
+    NilColon, // ~{~:}, run at least once
+    At,       // ~@{~}
+    Colon,    // ~:{~}, every element of the list is a sublist of args
+    AtColon,  // ~:@{~}, every rest arg is a sublist of args
+}
+
+#[doc = "CaseConvKind"]
+#[derive(Debug, PartialEq, Clone)]
+pub enum CaseConvKind {
+    Lower,           // ~(
+    Capitalize,      // ~:(
+    CapitalizeFirst, // ~@(
+    Upper,           // ~:@(
+}
+
+#[doc = "TildeParam"]
+#[derive(Debug, PartialEq, Clone)]
+pub enum TildeParam {
+    Arg,       // V, take the next arg
+    ArgsCount, // #, the number of the args left
+}
+
+#[doc = "ParamValue, the value of the `V` and `#` parameters resolved against the args"]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum ParamValue {
+    Int(i128),
+    Char(char),
+}
+
+impl ParamValue {
+    /// the value as the type of the parameter
+    fn to<T: TryFrom<i128>>(self) -> Result<T, TildeError> {
+        match self {
+            ParamValue::Int(n) => T::try_from(n).map_err(|_| {
+                TildeError::new(
+                    ErrorKind::OutOfRange,
+                    format!("parameter {} is out of range", n),
+                )
+            }),
+            ParamValue::Char(c) => Err(TildeError::new(
+                ErrorKind::RevealError,
+                format!("parameter '{} has to be an integer", c),
+            )),
+        }
+    }
+
+    /// the value as the char parameter
+    fn to_char(self) -> Result<char, TildeError> {
+        match self {
+            ParamValue::Char(c) => Ok(c),
+            ParamValue::Int(n) => Err(TildeError::new(
+                ErrorKind::RevealError,
+                format!("parameter {} has to be a char", n),
+            )),
+        }
+    }
+}
+
+impl TildeCondKind {
+    pub fn to_true(&mut self) {
+        match self {
+            TildeCondKind::Nil(_) => *self = TildeCondKind::Nil(true),
+            _ => (),
+        }
+    }
+}
+
+#[doc = "StarKind"]
+#[derive(Debug, PartialEq, Clone)]
+pub enum StarKind {
+    Hop(usize),  // ~n:*, back up n args
+    Skip(usize), // ~n*, skip n args
+    Goto(usize), // ~n@*, go to the n-th arg
+}
+
+#[doc = "CharKind"]
+#[derive(Debug, PartialEq, Clone)]
+pub enum CharKind {
+    Nil,     // ~C
+    At,      // ~@C, the #\a reader syntax
+    Colon,   // ~:C, spells out the name of non-printing chars
+    AtColon, // ~:@C, ~:C with the keyboard hint of control chars
+}
+
+#[doc = "Radix flag ~@R, ~:R, and ~:@R, also for ~@D, ~:D, and ~:@D"]
+#[derive(Debug, PartialEq, Clone)]
+pub enum RadixFlag {
+    At,      // ~@R
+    Colon,   // ~:R
+    AtColon, // ~:@R
+}
+
+#[doc = "TildeKind is the enum that including all potential kind.
+
+The most of variants inside has its implement trait. Like the `~d` is `TildeKind::Digit` and the type that can be revealed as `~a` should implement `TildeKindDigit` trait.
+
+Check README for custom types.
+"]
+#[derive(Debug, PartialEq, Clone)]
+#[allow(clippy::type_complexity)] // the parameters of every directive are listed in place
+pub enum TildeKind {
+    /// `~C`, `~@C`, `~:C`, and `~:@C`
+    Char(CharKind),
+
+    /// Tilde F: Fixed-format floating point, [doc](http://www.lispworks.com/documentation/lw50/CLHS/Body/22_cca.htm)
+    Float(
+        (
+            Option<usize>, // w
+            Option<usize>, // d
+            Option<isize>, // k
+            Option<char>,  // overflowchar
+            Option<char>,  // padchar
+            bool,          // @, print the sign even the number is positive
+        ),
+    ),
+
+    /// Tilde E: Exponential floating point, [doc](http://www.lispworks.com/documentation/lw50/CLHS/Body/22_ccb.htm)
+    Exponential(
+        (
+            Option<usize>, // w
+            Option<usize>, // d
+            Option<usize>, // e
+            Option<isize>, // k
+            Option<char>,  // overflowchar
+            Option<char>,  // padchar
+            Option<char>,  // exptchar
+            bool,          // @, print the sign even the number is positive
+        ),
+    ),
+
+    /// Tilde G: General floating point, [doc](http://www.lispworks.com/documentation/lw50/CLHS/Body/22_ccc.htm)
+    General(
+        (
+            Option<usize>, // w
+            Option<usize>, // d
+            Option<usize>, // e
+            Option<isize>, // k
+            Option<char>,  // overflowchar
+            Option<char>,  // padchar
+            Option<char>,  // exptchar
+            bool,          // @, print the sign even the number is positive
+        ),
+    ),
+
+    /// Tilde $: Monetary floating point, [doc](http://www.lispworks.com/documentation/lw50/CLHS/Body/22_ccd.htm)
+    Monetary(
+        (
+            Option<usize>, // d
+            Option<usize>, // n
+            Option<usize>, // w
+            Option<char>,  // padchar
+            bool,          // :, print the sign before the padding
+            bool,          // @, print the sign even the number is positive
+        ),
+    ),
+
+    /// Tilde R: Radix, [doc](http://www.lispworks.com/documentation/lw50/CLHS/Body/22_cba.htm)
+    Radix(
+        (
+            Option<u8>,        // radix
+            Option<usize>,     // mincol
+            Option<char>,      // padchar
+            Option<char>,      // commachar
+            Option<usize>,     // comma-interval
+            Option<RadixFlag>, // flag
+        ),
+    ),
+
+    /// Tilde D: Decimal, [doc](http://www.lispworks.com/documentation/lw50/CLHS/Body/22_cbb.htm)
+    Digit(
+        (
+            Option<usize>,     // mincol
+            Option<char>,      // padchar
+            Option<char>,      // commachar
+            Option<usize>,     // comma-interval
+            Option<RadixFlag>, // flag
+        ),
+    ),
+
+    /// Tilde B: Binary, [doc](http://www.lispworks.com/documentation/lw50/CLHS/Body/22_cbc.htm)
+    Binary(
+        (
+            Option<usize>,     // mincol
+            Option<char>,      // padchar
+            Option<char>,      // commachar
+            Option<usize>,     // comma-interval
+            Option<RadixFlag>, // flag
+        ),
+    ),
+
+    /// Tilde O: Octal, [doc](http://www.lispworks.com/documentation/lw50/CLHS/Body/22_cbd.htm)
+    Octal(
+        (
+            Option<usize>,     // mincol
+            Option<char>,      // padchar
+            Option<char>,      // commachar
+            Option<usize>,     // comma-interval
+            Option<RadixFlag>, // flag
+        ),
+    ),
+
+    /// Tilde X: Hexadecimal, [doc](http://www.lispworks.com/documentation/lw50/CLHS/Body/22_cbe.htm)
+    Hex(
+        (
+            Option<usize>,     // mincol
+            Option<char>,      // padchar
+            Option<char>,      // commachar
+            Option<usize>,     // comma-interval
+            Option<RadixFlag>, // flag
+        ),
+    ),
+
+    /// Tilde A: Aesthetic, `~mincol,colinc,minpad,padcharA`, None if there is no padding
+    Va(Option<(usize, usize, usize, char, bool)>),
+
+    /// `~n*`, `~n:*`, and `~n@*`
+    Star(StarKind),
+
+    /// Tilde S: Standard, `~mincol,colinc,minpad,padcharS`, None if there is no padding
+    Standard(Option<(usize, usize, usize, char, bool)>),
+
+    /// for loop expression, `~n{...~}` runs at most n times, the empty body takes the control string from the args
+    Loop(
+        (
+            Cow<'static, [Tilde]>,
+            TildeLoopKind,
+            Option<usize>, // the max iterations
+        ),
+    ),
+
+    /// Tilde ?: Recursive processing, `~?` and `~@?`, [doc](http://www.lispworks.com/documentation/lw50/CLHS/Body/22_cgf.htm)
+    Recursive(bool), // @, use the rest args instead of a list
+
+    /// Tilde P: Plural, [doc](http://www.lispworks.com/documentation/lw50/CLHS/Body/22_chc.htm)
+    Plural(
+        (
+            bool, // :, back up one argument
+            bool, // @, print y or ies
+        ),
+    ),
+
+    /// Tilde <: Justification, [doc](http://www.lispworks.com/documentation/lw50/CLHS/Body/22_cfb.htm)
+    Justify(
+        (
+            Cow<'static, [Tilde]>,       // segments, every one is VecTilde
+            (usize, usize, usize, char), // mincol, colinc, minpad, padchar
+            bool,                        // :, padding before the first segment
+            bool,                        // @, padding after the last segment
+            Option<(usize, usize)>,      // ~n,m:;, the first segment is the overflow prefix
+        ),
+    ),
+
+    /// Tilde (: Case conversion `~(...~)`, [doc](http://www.lispworks.com/documentation/lw50/CLHS/Body/22_cha.htm)
+    CaseConv((Cow<'static, [Tilde]>, CaseConvKind)),
+
+    /// Tilde ^: Escape upward, `~^`, `~n^`, `~n,m^`, `~n,m,p^`, and `~:^` for ending the whole `~:{` iteration
+    LoopEnd(
+        (
+            Option<i64>, // n
+            Option<i64>, // m
+            Option<i64>, // p
+            bool,        // :
+        ),
+    ),
+
+    /// tilde itself, `~n~`
+    Tildes(usize),
+
+    /// new line, `~n%`
+    Newline(usize),
+
+    /// fresh line, `~n&`, output n - 1 new lines if the output is at the beginning of a line
+    FreshLine(usize),
+
+    /// page separator, `~n|`
+    Page(usize),
+
+    /// Tilde T: Tabulate, [doc](http://www.lispworks.com/documentation/lw50/CLHS/Body/22_cfa.htm)
+    Tabulate(
+        (
+            Option<usize>, // colnum, or colrel with @
+            Option<usize>, // colinc
+            bool,          // @, relative tabulation
+        ),
+    ),
+
+    /// `~[` and `~]` condition
+    Cond((Cow<'static, [Tilde]>, TildeCondKind)),
+
+    /// text inside the tilde
+    Text(Cow<'static, str>),
+
+    /// Vec of tildes
+    VecTilde(Cow<'static, [Tilde]>),
+}
+
+impl TildeKind {
+    /// set the n-th parameter by the value of `V` or `#`, the nil value is the omitted parameter
+    pub fn set_param(&mut self, n: usize, value: Option<ParamValue>) -> Result<(), TildeError> {
+        let Some(v) = value else {
+            return Ok(());
+        };
+
+        match (self, n) {
+            (TildeKind::Float((w, d, k, overflowchar, padchar, _)), _) => match n {
+                0 => *w = Some(v.to()?),
+                1 => *d = Some(v.to()?),
+                2 => *k = Some(v.to()?),
+                3 => *overflowchar = Some(v.to_char()?),
+                4 => *padchar = Some(v.to_char()?),
+                _ => return Err(no_param(n)),
+            },
+            (
+                TildeKind::Exponential((w, d, e, k, overflowchar, padchar, exptchar, _))
+                | TildeKind::General((w, d, e, k, overflowchar, padchar, exptchar, _)),
+                _,
+            ) => match n {
+                0 => *w = Some(v.to()?),
+                1 => *d = Some(v.to()?),
+                2 => *e = Some(v.to()?),
+                3 => *k = Some(v.to()?),
+                4 => *overflowchar = Some(v.to_char()?),
+                5 => *padchar = Some(v.to_char()?),
+                6 => *exptchar = Some(v.to_char()?),
+                _ => return Err(no_param(n)),
+            },
+            (TildeKind::Monetary((d, digits, w, padchar, _, _)), _) => match n {
+                0 => *d = Some(v.to()?),
+                1 => *digits = Some(v.to()?),
+                2 => *w = Some(v.to()?),
+                3 => *padchar = Some(v.to_char()?),
+                _ => return Err(no_param(n)),
+            },
+            (TildeKind::Radix((radix, mincol, padchar, commachar, comma_interval, _)), _) => {
+                match n {
+                    0 => *radix = Some(v.to()?),
+                    1 => *mincol = Some(v.to()?),
+                    2 => *padchar = Some(v.to_char()?),
+                    3 => *commachar = Some(v.to_char()?),
+                    4 => *comma_interval = Some(v.to()?),
+                    _ => return Err(no_param(n)),
+                }
+            }
+            (
+                TildeKind::Digit((mincol, padchar, commachar, comma_interval, _))
+                | TildeKind::Binary((mincol, padchar, commachar, comma_interval, _))
+                | TildeKind::Octal((mincol, padchar, commachar, comma_interval, _))
+                | TildeKind::Hex((mincol, padchar, commachar, comma_interval, _)),
+                _,
+            ) => match n {
+                0 => *mincol = Some(v.to()?),
+                1 => *padchar = Some(v.to_char()?),
+                2 => *commachar = Some(v.to_char()?),
+                3 => *comma_interval = Some(v.to()?),
+                _ => return Err(no_param(n)),
+            },
+            (TildeKind::Va(padding) | TildeKind::Standard(padding), _) => {
+                let (mincol, colinc, minpad, padchar, _) =
+                    padding.get_or_insert((0, 1, 0, ' ', false));
+                match n {
+                    0 => *mincol = v.to()?,
+                    1 => *colinc = v.to()?,
+                    2 => *minpad = v.to()?,
+                    3 => *padchar = v.to_char()?,
+                    _ => return Err(no_param(n)),
+                }
+            }
+            (TildeKind::Justify((_, (mincol, colinc, minpad, padchar), _, _, _)), _) => match n {
+                0 => *mincol = v.to()?,
+                1 => *colinc = v.to()?,
+                2 => *minpad = v.to()?,
+                3 => *padchar = v.to_char()?,
+                _ => return Err(no_param(n)),
+            },
+            (TildeKind::Tabulate((colnum, colinc, _)), _) => match n {
+                0 => *colnum = Some(v.to()?),
+                1 => *colinc = Some(v.to()?),
+                _ => return Err(no_param(n)),
+            },
+            (TildeKind::LoopEnd((a, b, c, _)), _) => match n {
+                0 => *a = Some(v.to()?),
+                1 => *b = Some(v.to()?),
+                2 => *c = Some(v.to()?),
+                _ => return Err(no_param(n)),
+            },
+            (TildeKind::Star(StarKind::Hop(m) | StarKind::Skip(m) | StarKind::Goto(m)), 0)
+            | (
+                TildeKind::Tildes(m)
+                | TildeKind::Newline(m)
+                | TildeKind::FreshLine(m)
+                | TildeKind::Page(m),
+                0,
+            ) => *m = v.to()?,
+            (TildeKind::Loop((_, _, max)), 0) => *max = Some(v.to()?),
+            _ => return Err(no_param(n)),
+        }
+
+        Ok(())
+    }
+}
+
+fn no_param(n: usize) -> TildeError {
+    TildeError::new(
+        ErrorKind::RevealError,
+        format!("the tilde has no parameter {}", n),
+    )
+}
@@ -1,6 +1,6 @@
 use crate::tildes::*;
+use std::borrow::Cow;
 use std::fmt::Debug;
-use std::io::{BufRead, Cursor, Seek, SeekFrom};
 
 #[doc = r"The control string is the type contains control string for format.
 
@@ -13,64 +13,99 @@ Use `new(&str)` to create a ControlStr and reuse this control string instance to
 #[derive(Debug, Clone, PartialEq)]
 pub struct ControlStr<'a> {
     inner: &'a str,
-    tildes: Vec<((usize, usize), Tilde)>,
+    tildes: Cow<'a, [((usize, usize), Tilde)]>,
 }
 
 impl<'a> ControlStr<'a> {
     #[doc = r"Make a new ContrilStr from &str"]
     pub fn new(s: &'a str) -> Result<Self, Box<dyn std::error::Error + 'a>> {
-        let cc = Cursor::new(s);
-        let tildes = Self::scan(cc)?;
+        let tildes = Tilde::scan(s).map_err(|(_, e)| e)?;
 
-        Ok(Self { inner: s, tildes })
+        Ok(Self {
+            inner: s,
+            tildes: tildes.into(),
+        })
+    }
+
+    /// make the ControlStr from the tildes parsed already
+    pub(crate) fn from_parsed(s: &'a str, tildes: Vec<((usize, usize), Tilde)>) -> Self {
+        Self {
+            inner: s,
+            tildes: tildes.into(),
+        }
+    }
+
+    #[doc(hidden)]
+    /// make the ControlStr of the tildes parsed at compile time, used by `control_str!`
+    pub const fn from_static(s: &'a str, tildes: &'a [((usize, usize), Tilde)]) -> Self {
+        Self {
+            inner: s,
+            tildes: Cow::Borrowed(tildes),
+        }
     }
 
     #[allow(dead_code)]
     #[doc = "Reveal arguments to string"]
     pub fn reveal<'s, 'arg>(&self, args: Args<'s, 'arg>) -> Result<String, TildeError> {
         //dbg!(self);
-        let mut start = 0;
-        let end = self.inner.len();
-
         let mut result = String::with_capacity(args.left_count());
+        self.reveal_to(&args, &mut result)?;
+        Ok(result)
+    }
+
+    /// reveal the tildes and the texts between them to buf
+    pub(crate) fn reveal_to(
+        &self,
+        args: &dyn TildeAble,
+        buf: &mut String,
+    ) -> Result<Reveal, TildeError> {
+        let mut start = 0;
 
-        for (r, t) in &self.tildes {
-            result.push_str(&self.inner[start..r.0]);
-            t.reveal(&args, &mut result)?;
+        for (r, t) in self.tildes.iter() {
+            buf.push_str(&self.inner[start..r.0]);
+            // ~^ ends the whole control string
+            if let e @ Reveal::Escape(_) = t.reveal(args, buf)? {
+                return Ok(e);
+            }
             start = r.1;
         }
 
-        result += &self.inner[start..end];
+        buf.push_str(&self.inner[start..]);
 
-        Ok(result)
+        Ok(Reveal::Continue)
     }
 
-    fn scan(
-        mut s: Cursor<&'_ str>,
-    ) -> Result<Vec<((usize, usize), Tilde)>, Box<dyn std::error::Error + 'a>> {
-        let mut buf = vec![];
-        let mut has_read_len = 0;
-        let mut result = vec![];
-
-        loop {
-            //dbg!(s.position());
-            s.read_until(b'~', &mut buf)?;
-            match buf.last() {
-                // find the next '~'
-                Some(b'~') => {
-                    has_read_len += buf.len() - 1;
-                    s.seek(SeekFrom::Current(-1))?;
-                }
-                _ => return Ok(result),
-            }
+    /// the control string itself
+    pub fn as_str(&self) -> &'a str {
+        self.inner
+    }
 
-            let t = Tilde::parse(&mut s)?;
-            let end_index = has_read_len + t.len();
+    /// all tildes in order, the texts between them are `TildeKind::Text`
+    pub(crate) fn to_tildes(&self) -> Vec<Tilde> {
+        let mut start = 0;
+        let mut result = Vec::with_capacity(self.tildes.len() * 2 + 1);
+
+        for (r, t) in self.tildes.iter() {
+            if start != r.0 {
+                let text = &self.inner[start..r.0];
+                result.push(Tilde::new(
+                    text.len(),
+                    TildeKind::Text(text.to_string().into()),
+                ));
+            }
+            result.push(t.clone());
+            start = r.1;
+        }
 
-            result.push(((has_read_len, end_index), t));
-            has_read_len = end_index;
-            buf.clear();
+        if start != self.inner.len() {
+            let text = &self.inner[start..];
+            result.push(Tilde::new(
+                text.len(),
+                TildeKind::Text(text.to_string().into()),
+            ));
         }
+
+        result
     }
 }
 
@@ -124,36 +159,47 @@ mod tests {
     #[test]
     fn test_control_str_scan() -> Result<(), Box<dyn std::error::Error>> {
         let case = "hello wor~{~a~}";
-        let c = Cursor::new(case);
 
         assert_eq!(
-            ControlStr::scan(c)?,
+            Tilde::scan(case).map_err(|(_, e)| e)?,
             vec![(
                 (9, 15),
                 Tilde::new(
                     6,
-                    TildeKind::Loop((vec![Tilde::new(2, TildeKind::Va)], TildeLoopKind::Nil))
+                    TildeKind::Loop((
+                        vec![Tilde::new(2, TildeKind::Va(None))].into(),
+                        TildeLoopKind::Nil,
+                        None
+                    ))
                 )
             )]
         );
 
         let case = "~{~5$~}";
-        let c = Cursor::new(case);
 
         assert_eq!(
-            ControlStr::scan(c)?,
+            Tilde::scan(case).map_err(|(_, e)| e)?,
             vec![(
                 (0, 7),
                 Tilde::new(
                     7,
                     TildeKind::Loop((
-                        vec![Tilde::new(3, TildeKind::Float(Some("5".to_string())))],
-                        TildeLoopKind::Nil
+                        vec![Tilde::new(
+                            3,
+                            TildeKind::Monetary((Some(5), None, None, None, false, false))
+                        )]
+                        .into(),
+                        TildeLoopKind::Nil,
+                        None
                     ))
                 )
             )]
         );
 
+        // the start of the bad tilde
+        assert_eq!(Tilde::scan("~a ~{~a").unwrap_err().0, 3);
+        assert_eq!(Tilde::scan("~a ~a ~1c").unwrap_err().0, 6);
+
         Ok(())
     }
 
@@ -177,6 +223,198 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_reveal_loop_end() -> Result<(), Box<dyn std::error::Error>> {
+        // the top level
+        let cs = ControlStr::new("Done.~^ ~d warning~:p.")?;
+        assert_eq!("Done.".to_string(), cs.reveal(Args::new(vec![]))?);
+        assert_eq!(
+            "Done. 3 warnings.".to_string(),
+            cs.reveal([&3_i32 as &dyn TildeAble].into())?
+        );
+
+        // the parameters
+        let cs = ControlStr::new("a~1^b~1,1^c")?;
+        assert_eq!("ab".to_string(), cs.reveal(Args::new(vec![]))?);
+
+        // the parameters are positional, ~,0^ is not ~0^
+        let cs = ControlStr::new("a~,0^b~0,,0^c~0^d")?;
+        assert_eq!("abc".to_string(), cs.reveal(Args::new(vec![]))?);
+
+        let cs = ControlStr::new("~a~2,v,5^!")?;
+        assert_eq!(
+            "x".to_string(),
+            cs.reveal([&"x".to_string() as &dyn TildeAble, &3_i32].into())?
+        );
+        assert_eq!(
+            "x!".to_string(),
+            cs.reveal([&"x".to_string() as &dyn TildeAble, &7_i32].into())?
+        );
+
+        // inside the condition, it ends the loop outside
+        let cs = ControlStr::new("~{~a~#[.~^~:;, ~]~}")?;
+        let list = vec![&1_i32 as &dyn TildeAble, &2_i32, &3_i32];
+        assert_eq!(
+            "1, 2, 3.".to_string(),
+            cs.reveal([&list as &dyn TildeAble].into())?
+        );
+
+        // the output before ~^ is converted
+        let cs = ControlStr::new("~(~a~^ ~a~)!")?;
+        assert_eq!(
+            "hello".to_string(),
+            cs.reveal([&"HELLO".to_string() as &dyn TildeAble].into())?
+        );
+
+        // only the segments completely processed are justified
+        let cs = ControlStr::new("~10<~a~;~^~a~>|")?;
+        assert_eq!(
+            "        ab|".to_string(),
+            cs.reveal([&"ab".to_string() as &dyn TildeAble].into())?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reveal_sublist_loop() -> Result<(), Box<dyn std::error::Error>> {
+        let cs = ControlStr::new("~:{~a: ~d~:^, ~}")?;
+        let (a, b) = (
+            vec![&'a' as &dyn TildeAble, &1_i32],
+            vec![&'b' as &dyn TildeAble, &2_i32],
+        );
+        let list = vec![&a as &dyn TildeAble, &b];
+        assert_eq!(
+            "a: 1, b: 2".to_string(),
+            cs.reveal([&list as &dyn TildeAble].into())?
+        );
+
+        // the pairs from the data
+        let pairs = [(String::from("x"), 1_i32), (String::from("y"), 2_i32)];
+        let pairs = pairs
+            .iter()
+            .map(|(k, v)| vec![k as &dyn TildeAble, v])
+            .collect::<Vec<_>>();
+        let list = pairs
+            .iter()
+            .map(|p| p as &dyn TildeAble)
+            .collect::<Vec<_>>();
+        assert_eq!(
+            "x: 1, y: 2".to_string(),
+            cs.reveal([&list as &dyn TildeAble].into())?
+        );
+
+        // ~^ only ends the current sublist
+        let cs = ControlStr::new("~:{[~a~^ ~a]~}")?;
+        let c = vec![&3_i32 as &dyn TildeAble];
+        let list = vec![&a as &dyn TildeAble, &c, &b];
+        assert_eq!(
+            "[a 1][3[b 2]".to_string(),
+            cs.reveal([&list as &dyn TildeAble].into())?
+        );
+
+        let cs = ControlStr::new("~:@{~a=~a~:^ ~}.")?;
+        assert_eq!(
+            "a=1 b=2.".to_string(),
+            cs.reveal([&a as &dyn TildeAble, &b].into())?
+        );
+
+        // ~:^ inside the condition only ends the iteration at the last sublist
+        let cs = ControlStr::new("~:{~a~[~:^~;, ~]~}.")?;
+        let (a, b, c) = (
+            vec![&'a' as &dyn TildeAble, &0_usize],
+            vec![&'b' as &dyn TildeAble, &1_usize],
+            vec![&'c' as &dyn TildeAble, &0_usize],
+        );
+        let list = vec![&a as &dyn TildeAble, &b, &c];
+        assert_eq!(
+            "ab, c.".to_string(),
+            cs.reveal([&list as &dyn TildeAble].into())?
+        );
+
+        let list: Vec<&dyn TildeAble> = vec![];
+        let cs = ControlStr::new("~:{~a~}")?;
+        assert_eq!("".to_string(), cs.reveal([&list as &dyn TildeAble].into())?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reveal_loop_max() -> Result<(), Box<dyn std::error::Error>> {
+        let list = vec![&1_i32 as &dyn TildeAble, &2, &3, &4];
+
+        // ~^ only checks the args left, not the max
+        let cs = ControlStr::new("~2{~a~^, ~}")?;
+        assert_eq!(
+            "1, 2, ".to_string(),
+            cs.reveal([&list as &dyn TildeAble].into())?
+        );
+
+        let cs = ControlStr::new("~0{~a~}")?;
+        assert_eq!("".to_string(), cs.reveal([&list as &dyn TildeAble].into())?);
+
+        let cs = ControlStr::new("~3@{~a~}")?;
+        assert_eq!("123".to_string(), cs.reveal(list.as_slice().into())?);
+
+        let (a, b) = (vec![&'a' as &dyn TildeAble], vec![&'b' as &dyn TildeAble]);
+        let sublists = vec![&a as &dyn TildeAble, &b];
+        let cs = ControlStr::new("~1:{~a~}")?;
+        assert_eq!(
+            "a".to_string(),
+            cs.reveal([&sublists as &dyn TildeAble].into())?
+        );
+
+        // the max stops ~:@{ before taking the next sublist
+        let x = String::from("x");
+        let cs = ControlStr::new("~1:@{[~a]~}|~{~a~}~a")?;
+        assert_eq!(
+            "[a]|bx".to_string(),
+            cs.reveal([&a as &dyn TildeAble, &b, &x].into())?
+        );
+
+        // the last sublist under the max ends the iteration by ~:^
+        let cs = ControlStr::new("~2:{~a~:^, ~}.")?;
+        let c = vec![&'c' as &dyn TildeAble];
+        let sublists = vec![&a as &dyn TildeAble, &b, &c];
+        assert_eq!(
+            "a, b.".to_string(),
+            cs.reveal([&sublists as &dyn TildeAble].into())?
+        );
+        let cs = ControlStr::new("~2:@{~a~:^, ~}.")?;
+        assert_eq!(
+            "a, b.".to_string(),
+            cs.reveal([&a as &dyn TildeAble, &b, &c].into())?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reveal_loop_body_arg() -> Result<(), Box<dyn std::error::Error>> {
+        let list = vec![&1_i32 as &dyn TildeAble, &2, &3];
+
+        let body = String::from("<~a>");
+        let cs = ControlStr::new("~{~}")?;
+        assert_eq!(
+            "<1><2><3>".to_string(),
+            cs.reveal([&body as &dyn TildeAble, &list].into())?
+        );
+
+        let cs = ControlStr::new("~@{~}!")?;
+        assert_eq!(
+            "1;2;!".to_string(),
+            cs.reveal([&"~a;" as &dyn TildeAble, &1_i32, &2_i32].into())?
+        );
+
+        let cs = ControlStr::new("~2{~}")?;
+        assert_eq!(
+            "1, 2, ".to_string(),
+            cs.reveal([&"~a, " as &dyn TildeAble, &list].into())?
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_reveal_loop_tildes() -> Result<(), Box<dyn std::error::Error>> {
         let case = "hello wor~{~a~}~a";
@@ -353,6 +591,18 @@ mod tests {
             parse_test_result(reveal_tildes(&cs, &(args.into())))?
         );
 
+        // the clause takes the rest args
+        let cs = ControlStr::new("~[zero~;~:d~]!")?;
+        assert_eq!(
+            "1,000!".to_string(),
+            cs.reveal([&1_usize as &dyn TildeAble, &1000_i32].into())?
+        );
+        let cs = ControlStr::new("~:[no~;~:d~]!")?;
+        assert_eq!(
+            "no!".to_string(),
+            cs.reveal([&false as &dyn TildeAble, &1000_i32].into())?
+        );
+
         Ok(())
     }
 
@@ -546,6 +796,33 @@ mod tests {
             parse_test_result(reveal_tildes(&cs, &(arg.into())))?
         );
 
+        // reuse the args in different orders
+        let cs = ControlStr::new("~1@*~d ~0@*~a")?;
+        assert_eq!(
+            "3 apples".to_string(),
+            cs.reveal([&"apples".to_string() as &dyn TildeAble, &3_i32].into())?
+        );
+
+        let cs = ControlStr::new("~*~d ~2:*~a, ~:*~a ~d")?;
+        assert_eq!(
+            "3 apples, apples 3".to_string(),
+            cs.reveal([&"apples".to_string() as &dyn TildeAble, &3_i32].into())?
+        );
+
+        // the jump inside the loop is relative to the loop's args
+        let cs = ControlStr::new("~a ~{~1@*~d~}")?;
+        let a = vec![&1_i64 as &dyn TildeAble, &2];
+        assert_eq!(
+            "x 2".to_string(),
+            cs.reveal([&"x".to_string() as &dyn TildeAble, &a].into())?
+        );
+
+        let cs = ControlStr::new("~d ~2:*~d")?;
+        assert!(cs.reveal([&1_i64 as &dyn TildeAble].into()).is_err());
+
+        let cs = ControlStr::new("~3@*")?;
+        assert!(cs.reveal([&1_i64 as &dyn TildeAble].into()).is_err());
+
         Ok(())
     }
 
@@ -564,11 +841,40 @@ mod tests {
             vec![
                 Some("a".to_string()),
                 Some("b".to_string()),
-                Some("'c'".to_string())
+                Some("#\\c".to_string())
             ],
             parse_test_result(reveal_tildes(&cs, &(arg.into())))?
         );
 
+        let cs = ControlStr::new("~:C|~:C|~:C|~:C|~:C|~:C|~:C")?;
+        assert_eq!(
+            "a|Space|Newline|Tab|Nul|Rubout|é".to_string(),
+            cs.reveal(
+                [
+                    &'a' as &dyn TildeAble,
+                    &' ',
+                    &'\n',
+                    &'\t',
+                    &'\0',
+                    &'\u{7f}',
+                    &'é'
+                ]
+                .into()
+            )?
+        );
+
+        let cs = ControlStr::new("~@C ~@C ~@C")?;
+        assert_eq!(
+            "#\\a #\\Space #\\Return".to_string(),
+            cs.reveal([&'a' as &dyn TildeAble, &' ', &'\r'].into())?
+        );
+
+        let cs = ControlStr::new("~:@C, ~:@C, ~:@C, ~:@C")?;
+        assert_eq!(
+            "Tab (Control-I), Nul (Control-@), Rubout (Control-?), a".to_string(),
+            cs.reveal([&'\t' as &dyn TildeAble, &'\0', &'\u{7f}', &'a'].into())?
+        );
+
         Ok(())
     }
 
@@ -639,10 +945,638 @@ mod tests {
             ControlStr::new("~10,,,|,2:R")?.reveal([&65535_usize as &dyn TildeAble].into())?
         );
 
+        // the large integers are not truncated
+        assert_eq!(
+            "1180591620717411303424|-80000000000000000000000000000000".to_string(),
+            ControlStr::new("~10R|~16R")?
+                .reveal([&(1_u128 << 70) as &dyn TildeAble, &i128::MIN].into())?
+        );
+
+        assert_eq!(
+            "eighteen quintillion four hundred forty-six quadrillion seven hundred forty-four trillion seventy-three billion seven hundred nine million five hundred fifty-one thousand six hundred twenty-one".to_string(),
+            ControlStr::new("~R")?.reveal([&((1_u128 << 64) + 5) as &dyn TildeAble].into())?
+        );
+
+        // the radix is in 2..=36
+        assert_eq!(
+            "z|1010".to_string(),
+            ControlStr::new("~36R|~2R")?.reveal([&35_i32 as &dyn TildeAble, &10_i32].into())?
+        );
+        for case in ["~37R", "~1R", "~0R"] {
+            let e = ControlStr::new(case)?
+                .reveal([&10_i32 as &dyn TildeAble].into())
+                .unwrap_err();
+            assert!(matches!(e.kind(), ErrorKind::OutOfRange), "{case}");
+        }
+        let e = ControlStr::new("~vR")?
+            .reveal([&37_i32 as &dyn TildeAble, &10_i32].into())
+            .unwrap_err();
+        assert!(matches!(e.kind(), ErrorKind::OutOfRange));
+
+        // the modifiers in either order
+        assert_eq!(
+            "IIII|IIII".to_string(),
+            ControlStr::new("~:@R|~@:R")?.reveal([&4_i32 as &dyn TildeAble, &4_i32].into())?
+        );
+
         //:= Next: more tests here
         Ok(())
     }
 
+    #[test]
+    fn test_reveal_roman() -> Result<(), Box<dyn std::error::Error>> {
+        let cs = ControlStr::new("~@R ~:@R")?;
+        assert_eq!(
+            "XIV XIIII".to_string(),
+            cs.reveal([&14_i32 as &dyn TildeAble, &14_u8].into())?
+        );
+
+        assert_eq!(
+            "MCMXCIX MDCCCCLXXXXVIIII".to_string(),
+            cs.reveal([&1999_usize as &dyn TildeAble, &1999_i64].into())?
+        );
+
+        assert_eq!(
+            "MMMCMXCIX MMMMDCCCCLXXXXVIIII".to_string(),
+            cs.reveal([&3999_u16 as &dyn TildeAble, &4999_isize].into())?
+        );
+
+        // out of range
+        for (cs, n) in [
+            ("~@R", &4000_i32 as &dyn TildeAble),
+            ("~:@R", &5000_u32),
+            ("~@R", &0_u64),
+            ("~:@R", &-1_i16),
+            ("~@R", &((1_u128 << 64) + 5)),
+            ("~@R", &i128::MIN),
+        ] {
+            let e = ControlStr::new(cs)?.reveal([n].into()).unwrap_err();
+            assert!(matches!(e.kind(), ErrorKind::OutOfRange));
+        }
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn test_reveal_float() -> Result<(), Box<dyn std::error::Error>> {
+        // examples from the CLHS 22.3.11
+        let cs = ControlStr::new("~6,2F|~6,2,1,'*F|~6,2,,'?F|~6F|~,2F|~F")?;
+        assert_eq!(
+            "  3.14| 31.42|  3.14|3.1416|3.14|3.14159".to_string(),
+            cs.reveal([&3.14159_f32 as &dyn TildeAble; 6].into())?
+        );
+
+        assert_eq!(
+            " -3.14|-31.42| -3.14|-3.142|-3.14|-3.14159".to_string(),
+            cs.reveal([&-3.14159_f64 as &dyn TildeAble; 6].into())?
+        );
+
+        assert_eq!(
+            "100.00|******|100.00| 100.0|100.00|100.0".to_string(),
+            cs.reveal([&100.0_f64 as &dyn TildeAble; 6].into())?
+        );
+
+        assert_eq!(
+            "1234.00|******|??????|1234.0|1234.00|1234.0".to_string(),
+            cs.reveal([&1234.0_f32 as &dyn TildeAble; 6].into())?
+        );
+
+        assert_eq!(
+            "  0.01|  0.06|  0.01| 0.006|0.01|0.006".to_string(),
+            cs.reveal([&0.006_f64 as &dyn TildeAble; 6].into())?
+        );
+
+        assert_eq!(
+            "+2.68|2.|0.0000001|.50|0.5".to_string(),
+            ControlStr::new("~,2@F|~,0F|~F|~3,2F|~3F")?.reveal(
+                [
+                    &2.675_f64 as &dyn TildeAble,
+                    &1.5_f64,
+                    &1e-7_f64,
+                    &0.5_f64,
+                    &0.5_f32
+                ]
+                .into()
+            )?
+        );
+
+        assert_eq!(
+            "0005.0|-3.00|inf".to_string(),
+            ControlStr::new("~6,,,,'0F|~,2F|~F")?
+                .reveal([&5_i32 as &dyn TildeAble, &-3_i64, &f64::INFINITY].into())?
+        );
+
+        // the integers keep all digits
+        assert_eq!(
+            "9007199254740993.0|18446744073709551615.00".to_string(),
+            ControlStr::new("~F|~,2F")?
+                .reveal([&9007199254740993_i64 as &dyn TildeAble, &u64::MAX].into())?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn test_reveal_exponential() -> Result<(), Box<dyn std::error::Error>> {
+        // examples from the CLHS 22.3.11, the exponent char follows SBCL
+        let cs = ControlStr::new("~9,2,1,,'*E|~10,3,2,2,'?,,'$E|~9,3,2,-2,'%@E|~9,2E")?;
+        assert_eq!(
+            "  3.14e+0| 31.42$-01|+.003e+03|  3.14e+0".to_string(),
+            cs.reveal([&3.14159_f32 as &dyn TildeAble; 4].into())?
+        );
+
+        assert_eq!(
+            " -3.14e+0|-31.42$-01|-.003e+03| -3.14e+0".to_string(),
+            cs.reveal([&-3.14159_f32 as &dyn TildeAble; 4].into())?
+        );
+
+        assert_eq!(
+            "  1.10d+3| 11.00$+02|+.001d+06|  1.10d+3".to_string(),
+            cs.reveal([&1100.0_f64 as &dyn TildeAble; 4].into())?
+        );
+
+        assert_eq!(
+            "*********| 11.00$+12|+.001e+16| 1.10e+13".to_string(),
+            cs.reveal([&1.1e13_f32 as &dyn TildeAble; 4].into())?
+        );
+
+        assert_eq!(
+            "*********|??????????|%%%%%%%%%|1.10d+120".to_string(),
+            cs.reveal([&1.1e120_f64 as &dyn TildeAble; 4].into())?
+        );
+
+        assert_eq!(
+            "3.14159e+0|1.1d+3|0.0e+0|1.0d+1|3.142d+0|-1.5E-7|inf".to_string(),
+            ControlStr::new("~E|~E|~E|~,1E|~8E|~,,,,,,'EE|~E")?.reveal(
+                [
+                    &3.14159_f32 as &dyn TildeAble,
+                    &1100.0_f64,
+                    &0.0_f32,
+                    &9.96_f64,
+                    &3.14159_f64,
+                    &-1.5e-7_f64,
+                    &f32::INFINITY
+                ]
+                .into()
+            )?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn test_reveal_general() -> Result<(), Box<dyn std::error::Error>> {
+        // examples from the CLHS 22.3.11, the exponent char follows SBCL
+        let cs = ControlStr::new("~9,2,1,,'*G|~9,3,2,3,'?,,'$G|~9,3,2,0,'%G|~9,2G")?;
+        let cases: [(&dyn TildeAble, &str); 9] = [
+            (&0.0314159_f32, "  3.14e-2|314.2$-04|0.314e-01|  3.14e-2"),
+            (&0.314159_f32, "  0.31   |0.314    |0.314    | 0.31    "),
+            (&3.14159_f64, "   3.1   | 3.14    | 3.14    |  3.1    "),
+            (&31.4159_f32, "   31.   | 31.4    | 31.4    |  31.    "),
+            (&314.159_f32, "  3.14e+2| 314.    | 314.    |  3.14e+2"),
+            (&3141.59_f32, "  3.14e+3|314.2$+01|0.314e+04|  3.14e+3"),
+            (&3141.59_f64, "  3.14d+3|314.2$+01|0.314d+04|  3.14d+3"),
+            (&3.14e12_f32, "*********|314.0$+10|0.314e+13| 3.14e+12"),
+            (&3.14e120_f64, "*********|?????????|%%%%%%%%%|3.14d+120"),
+        ];
+        for (arg, expect) in cases {
+            assert_eq!(expect.to_string(), cs.reveal([arg; 4].into())?);
+        }
+
+        assert_eq!(
+            "1.0d-9|123.456    |1.5d+12|0.0    |+12.    ".to_string(),
+            ControlStr::new("~G|~G|~G|~G|~@G")?.reveal(
+                [
+                    &1e-9_f64 as &dyn TildeAble,
+                    &123.456_f64,
+                    &1.5e12_f64,
+                    &0.0_f32,
+                    &12.0_f32
+                ]
+                .into()
+            )?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    #[allow(clippy::approx_constant)]
+    fn test_reveal_monetary() -> Result<(), Box<dyn std::error::Error>> {
+        let cs = ControlStr::new("~$|~2,4,10$|~,,10:@$|~,,10@$|~,,8,'*$|~,0$|~3$")?;
+        assert_eq!(
+            "3.14|   0003.14|+     3.00|     -3.00|***12.50|.50|1.000".to_string(),
+            cs.reveal(
+                [
+                    &3.14159_f64 as &dyn TildeAble,
+                    &3.14159_f32,
+                    &3.0_f64,
+                    &-3_i32,
+                    &12.5_f64,
+                    &0.5_f32,
+                    &1_u8
+                ]
+                .into()
+            )?
+        );
+
+        assert_eq!(
+            "1234567.89|-0.01|-   1.00".to_string(),
+            ControlStr::new("~$|~$|~,,8:$")?
+                .reveal([&1234567.891_f64 as &dyn TildeAble, &-0.005_f64, &-1_i64].into())?
+        );
+
+        // the integers keep all digits
+        assert_eq!(
+            "9007199254740993.00|-170141183460469231731687303715884105728.0".to_string(),
+            ControlStr::new("~$|~1$")?
+                .reveal([&9007199254740993_i64 as &dyn TildeAble, &i128::MIN].into())?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reveal_digit() -> Result<(), Box<dyn std::error::Error>> {
+        let cs = ControlStr::new("~d|~:d|~@d|~:@d|~8d|~8,'0d|~,,'.,4:d|~8:@d")?;
+        assert_eq!(
+            "-5|1,000,000|+42|+1,234|     -12|00000012|1.2345.6789|  -1,234".to_string(),
+            cs.reveal(
+                [
+                    &-5_i8 as &dyn TildeAble,
+                    &1000000_u32,
+                    &42_usize,
+                    &1234_i64,
+                    &-12_i16,
+                    &12_u8,
+                    &123456789_u64,
+                    &-1234_isize
+                ]
+                .into()
+            )?
+        );
+
+        assert_eq!(
+            "170,141,183,460,469,231,731,687,303,715,884,105,727|0|100".to_string(),
+            ControlStr::new("~:d|~:d|~:d")?
+                .reveal([&i128::MAX as &dyn TildeAble, &0_u128, &100_i32].into())?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reveal_binary_octal_hex() -> Result<(), Box<dyn std::error::Error>> {
+        let cs = ControlStr::new("~8,'0X|~x|~@X|~,,' ,4:B|~b|~o|~8,'0o|~:@x")?;
+        assert_eq!(
+            "000000FF|-1F|+0|1010 1010|-10000000|777|00000-17|+FF,FFF,FFF".to_string(),
+            cs.reveal(
+                [
+                    &255_u8 as &dyn TildeAble,
+                    &-31_i32,
+                    &0_usize,
+                    &0b10101010_u16,
+                    &i8::MIN,
+                    &511_u64,
+                    &-15_isize,
+                    &u32::MAX,
+                ]
+                .into()
+            )?
+        );
+
+        assert_eq!(
+            "FFFFFFFFFFFFFFFFFFFFFFFFFFFFFFFF|-80000000000000000000000000000000".to_string(),
+            ControlStr::new("~X|~X")?.reveal([&u128::MAX as &dyn TildeAble, &i128::MIN].into())?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reveal_padding() -> Result<(), Box<dyn std::error::Error>> {
+        let cs = ControlStr::new("~6a|~6@a|~6,4a|~2,,3,'*a|~8s|~8@s|")?;
+        assert_eq!(
+            "ab    |    ab|abc    |abc***|'c'     |   \"str\"|".to_string(),
+            cs.reveal(
+                [
+                    &"ab".to_string() as &dyn TildeAble,
+                    &"ab".to_string(),
+                    &"abc".to_string(),
+                    &"abc".to_string(),
+                    &'c',
+                    &"str".to_string()
+                ]
+                .into()
+            )?
+        );
+
+        assert_eq!(
+            "1     |true  |toolongtext".to_string(),
+            ControlStr::new("~6a|~6a|~6a")?
+                .reveal([&1_i32 as &dyn TildeAble, &true, &"toolongtext".to_string()].into())?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reveal_case_conv() -> Result<(), Box<dyn std::error::Error>> {
+        let cs = ControlStr::new("~(~a~)|~:(~a~)|~@(~a~)|~:@(~a~)")?;
+        let s = "tHE quick-brown 2nd FOX".to_string();
+        assert_eq!(
+            "the quick-brown 2nd fox|The Quick-Brown 2nd Fox|The quick-brown 2nd fox|THE QUICK-BROWN 2ND FOX"
+                .to_string(),
+            cs.reveal([&s as &dyn TildeAble; 4].into())?
+        );
+
+        // the loop and condition inside, and nested case conversion
+        let (apple, banana) = ("apple".to_string(), "BANANA".to_string());
+        let list = vec![&apple as &dyn TildeAble, &banana];
+        assert_eq!(
+            "Fruits: apple, banana. SHOW ME".to_string(),
+            ControlStr::new("~@(fruits: ~{~a~^, ~}.~) ~:@(~[hide~;show~] ~(ME~)~)")?
+                .reveal([&list as &dyn TildeAble, &1_usize].into())?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reveal_plural() -> Result<(), Box<dyn std::error::Error>> {
+        let cs = ControlStr::new("~d file~:p, ~d director~:@p, ~d tr~@p")?;
+        assert_eq!(
+            "1 file, 3 directories, 0 tries".to_string(),
+            cs.reveal([&1_i32 as &dyn TildeAble, &3_usize, &0_u8, &0_u8].into())?
+        );
+
+        assert_eq!(
+            "3 files, 1 directory, 1 try".to_string(),
+            cs.reveal([&3_i64 as &dyn TildeAble, &1_u32, &1_i8, &1_i8].into())?
+        );
+
+        let cs = ControlStr::new("~{~d dog~:p~^, ~}")?;
+        let list = vec![&1_i32 as &dyn TildeAble, &2_i32];
+        assert_eq!(
+            "1 dog, 2 dogs".to_string(),
+            cs.reveal([&list as &dyn TildeAble].into())?
+        );
+
+        // ~:P needs the arg before it
+        let e = ControlStr::new("a~:p")?
+            .reveal([&1_i32 as &dyn TildeAble].into())
+            .unwrap_err();
+        assert!(matches!(e.kind(), ErrorKind::FormatError));
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reveal_params() -> Result<(), Box<dyn std::error::Error>> {
+        let cs = ControlStr::new("~v,vd|~vA|~v,,,va|~#%~a")?;
+        assert_eq!(
+            "00042|abc   |abc-|\n1".to_string(),
+            cs.reveal(
+                [
+                    &5_usize as &dyn TildeAble,
+                    &'0',
+                    &42_i32,
+                    &6_i32,
+                    &"abc".to_string(),
+                    &4_u8,
+                    &'-',
+                    &"abc".to_string(),
+                    &1_i32,
+                ]
+                .into()
+            )?
+        );
+
+        // the args of the loop
+        let cs = ControlStr::new("~{~vR~^ ~}")?;
+        let list = vec![&2_usize as &dyn TildeAble, &5_i32, &16_usize, &255_i32];
+        assert_eq!(
+            "101 ff".to_string(),
+            cs.reveal([&list as &dyn TildeAble].into())?
+        );
+
+        // the omitted trailing parameters of ~R
+        let cs = ControlStr::new("~v,8,'0R|~2,v,'0:R|~v,,,v,v:R")?;
+        assert_eq!(
+            "000000ff|0000000001,010|1_0000".to_string(),
+            cs.reveal(
+                [
+                    &16_usize as &dyn TildeAble,
+                    &255_i32,
+                    &14_usize,
+                    &10_i32,
+                    &2_usize,
+                    &'_',
+                    &4_usize,
+                    &16_i32,
+                ]
+                .into()
+            )?
+        );
+
+        let cs = ControlStr::new("~vd")?;
+        assert!(cs
+            .reveal([&"a".to_string() as &dyn TildeAble, &1_i32].into())
+            .is_err());
+        assert!(cs.reveal([&'a' as &dyn TildeAble, &1_i32].into()).is_err());
+
+        // the nil V is the omitted parameter
+        let cs = ControlStr::new("~v,'0d|~va|")?;
+        let nil: Option<&dyn TildeAble> = None;
+        assert_eq!(
+            "42|abc|".to_string(),
+            cs.reveal([&nil as &dyn TildeAble, &42_i32, &nil, &"abc".to_string()].into())?
+        );
+
+        // ~v^ escapes the loop
+        let cs = ControlStr::new("~{~a~v^ ~}")?;
+        let list = vec![&1_i32 as &dyn TildeAble, &1_i32, &2_i32, &0_i32];
+        assert_eq!(
+            "1 2".to_string(),
+            cs.reveal([&list as &dyn TildeAble].into())?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reveal_recursive() -> Result<(), Box<dyn std::error::Error>> {
+        let cs = ControlStr::new("~? ~d")?;
+        let ctrl = "<~a~a>";
+        let list = vec![&1_i32 as &dyn TildeAble, &2_i32];
+        assert_eq!(
+            "<12> 3".to_string(),
+            cs.reveal([&ctrl as &dyn TildeAble, &list, &3_i32].into())?
+        );
+
+        // the rest args are used by ~@?
+        let cs = ControlStr::new("~@? ~d")?;
+        let ctrl = String::from("<~a~a>");
+        assert_eq!(
+            "<12> 3".to_string(),
+            cs.reveal([&ctrl as &dyn TildeAble, &1_i32, &2_i32, &3_i32].into())?
+        );
+
+        // the ControlStr can be reused as an arg
+        let inner = ControlStr::new("~d item~:p")?;
+        let cs = ControlStr::new("~{~@?~^, ~}")?;
+        let list = vec![&inner as &dyn TildeAble, &1_i32, &inner, &2_i32];
+        assert_eq!(
+            "1 item, 2 items".to_string(),
+            cs.reveal([&list as &dyn TildeAble].into())?
+        );
+
+        // the tildes of the ControlStr are used, not parsed again
+        let inner = ControlStr::from_parsed(
+            "~a",
+            vec![((0, 2), Tilde::new(2, TildeKind::Standard(None)))],
+        );
+        let x = String::from("x");
+        let list = vec![&x as &dyn TildeAble];
+        let cs = ControlStr::new("~?|~{~}")?;
+        assert_eq!(
+            "\"x\"|\"x\"".to_string(),
+            cs.reveal([&inner as &dyn TildeAble, &list, &inner, &list].into())?
+        );
+
+        let cs = ControlStr::new("~?")?;
+        let ctrl = "~1P";
+        assert!(cs.reveal([&ctrl as &dyn TildeAble, &list].into()).is_err());
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reveal_newline() -> Result<(), Box<dyn std::error::Error>> {
+        let cs = ControlStr::new("~&a~%b~2%c~&~&d~3&e~0&~|~2|~~~3~")?;
+        assert_eq!(
+            "a\nb\n\nc\nd\n\n\ne\u{c}\u{c}\u{c}~~~~".to_string(),
+            cs.reveal(Args::new(vec![]))?
+        );
+
+        // fresh line depends on what has been output, including the args
+        let cs = ControlStr::new("~a~&~a~&end")?;
+        assert_eq!(
+            "line\nnext\nend".to_string(),
+            cs.reveal([&"line\n".to_string() as &dyn TildeAble, &"next".to_string()].into())?
+        );
+
+        // and the output before the justification and the case conversion
+        let cs = ControlStr::new("ab~<~&x~>|cd~(~&Y~)|~<~&z~>")?;
+        assert_eq!("ab\nx|cd\ny|\nz".to_string(), cs.reveal(Args::new(vec![]))?);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reveal_tilde_newline() -> Result<(), Box<dyn std::error::Error>> {
+        let cs = ControlStr::new(
+            "~a, ~
+             ~a~:
+             ~a~@
+             ~a",
+        )?;
+        assert_eq!(
+            "1, 2             3\n4".to_string(),
+            cs.reveal([&1_i32 as &dyn TildeAble, &2, &3, &4].into())?
+        );
+
+        let cs = ControlStr::new(
+            "~{~a~^, ~
+             ~}",
+        )?;
+        let list = vec![&1_i32 as &dyn TildeAble, &2, &3];
+        assert_eq!(
+            "1, 2, 3".to_string(),
+            cs.reveal([&list as &dyn TildeAble].into())?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reveal_tabulate() -> Result<(), Box<dyn std::error::Error>> {
+        let cs = ControlStr::new("~{~a~8T~a~%~}")?;
+        let (h, v, help, verbose) = (
+            "-h".to_string(),
+            "--verbose".to_string(),
+            "help".to_string(),
+            "verbose".to_string(),
+        );
+        let list = vec![&h as &dyn TildeAble, &help, &v, &verbose];
+        assert_eq!(
+            "-h      help\n--verbose verbose\n".to_string(),
+            cs.reveal([&list as &dyn TildeAble].into())?
+        );
+
+        // the column is counted from the last new line
+        assert_eq!(
+            "ab\nc   d|abcdef    |abc  |ab   x|ax".to_string(),
+            ControlStr::new("ab~%c~4Td|abcdef~4,4T|abc~2@T|ab~1,4@Tx|a~0,0@Tx")?
+                .reveal(Args::new(vec![]))?
+        );
+
+        assert_eq!(
+            "  |abc|abc x".to_string(),
+            ControlStr::new("~2T|abc~4,0T|abc~@[ x~]")?
+                .reveal([&Some(&1 as &dyn TildeAble) as &dyn TildeAble].into())?
+        );
+
+        // the column inside the justification and the case conversion is the column of the output
+        assert_eq!(
+            "ab  x|     ab   y    |ab z".to_string(),
+            ControlStr::new("ab~<~4Tx~>|~15:@<ab~11Ty~>|~(AB~12TZ~)")?.reveal(Args::new(vec![]))?
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_reveal_justify() -> Result<(), Box<dyn std::error::Error>> {
+        // examples from the CLHS 22.3.6.2
+        let cs = ControlStr::new("~10<foo~;bar~>|~10:<foo~;bar~>|~10<foobar~>|~10:<foobar~>|~10:@<foo~;bar~>|~10@<foobar~>|~10:@<foobar~>")?;
+        assert_eq!(
+            "foo    bar|  foo  bar|    foobar|    foobar|  foo bar |foobar    |  foobar  "
+                .to_string(),
+            cs.reveal(Args::new(vec![]))?
+        );
+
+        // centred banner and the args inside the segments
+        let title = "Report".to_string();
+        assert_eq!(
+            "*******Report*******|left         1,000".to_string(),
+            ControlStr::new("~20,,,'*:@<~a~>|~18<~a~;~:d~>")?
+                .reveal([&title as &dyn TildeAble, &"left".to_string(), &1000_i32].into())?
+        );
+
+        // the overflow prefix is output when the line would be longer than 12
+        let cs = ControlStr::new("~a~<~%;; ~2,12:;~a~>")?;
+        assert_eq!(
+            "0123456789\n;; more".to_string(),
+            cs.reveal(
+                [
+                    &"0123456789".to_string() as &dyn TildeAble,
+                    &"more".to_string()
+                ]
+                .into()
+            )?
+        );
+        assert_eq!(
+            "01more".to_string(),
+            cs.reveal([&"01".to_string() as &dyn TildeAble, &"more".to_string()].into())?
+        );
+
+        Ok(())
+    }
+
     #[test]
     fn test_reveal() -> Result<(), Box<dyn std::error::Error>> {
         let case = "~a, ~a, ~a";
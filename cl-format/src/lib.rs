@@ -19,6 +19,8 @@ assert_eq!(String::from("1, 2, 3"), a.unwrap());
 
 All arguments used for formatting have to be borrowed, and they must implement the `TildeAble` trait. Check the [Implement for custom type](#implement-for-custom-type) section for more details.
 
+The string literal control string is parsed at compile time, so a typo like `cl_format!("~{~a", &l)` is a compile error. The number of arguments and their types are checked at compile time too, like `format!` does: `cl_format!("~a and ~a", &1)` misses an argument, and `cl_format!("~d", &s)` with a `String` doesn't compile because `String` doesn't implement `TildeKindDigit`. The control string made at runtime is parsed and checked when calling `cl_format!`. The `&dyn TildeAble` argument, like `tilde!(&s)` and the elements of `Vec<&dyn TildeAble>`, is the exception: its type is erased, so it passes the compile time check of every tilde, and `cl_format!("~d", tilde!(&s))` compiles but returns the `RevealError` at runtime.
+
 Here is more usage of the macro. Escaping the double quote symbol for strings:
 
 ```rust
@@ -62,7 +64,7 @@ let a = cl_format!("The value is:\n ~a", &l);
 assert_eq!(String::from("The value is:\n [1, 2, 3]"), a.unwrap());
 ```
 
-Now, we have some inconsistency between Common Lisp and Rust. In Common Lisp, `~%` in the control string is the new line, but we are in Rust now, so `\n` is going to work.
+In Common Lisp, `~%` in the control string is the new line. We are in Rust now, so both `~%` and `\n` work.
 
 I think I am a bit tired of showing the type as `&dyn TildeAble` to elements inside Vec. But I haven't found a way to avoid it yet. If you know, let me know. So I added some macros:
 
@@ -82,6 +84,59 @@ let a = cl_format!("~@{~a~^, ~}", &1, &2, &3);
 assert_eq!(String::from("1, 2, 3"), a.unwrap());
 ```
 
+Other types implementing `Display` or `Debug`, like `&str`, `Cow<str>`, and `PathBuf`, can be wrapped by `Disp` for `~a` and `Dbg` for `~s`. `tilde!(disp: &a)` and `tilde!(dbg: &a)` wrap and add the `&dyn TildeAble`, the wrapper is a temporary like other `&` expressions:
+
+```rust
+use cl_format::*;
+
+use std::path::PathBuf;
+
+let name = "cl-format";
+let path = PathBuf::from("/tmp");
+let a = cl_format!("~a in ~s", &Disp(name), &Dbg(&path));
+assert_eq!(String::from("cl-format in \"/tmp\""), a.unwrap());
+
+let l = [tilde!(disp: &name), tilde!(dbg: &path), &1];
+let a = cl_format!("~{~a, ~s, ~a~}", &l.to_vec());
+assert_eq!(String::from("cl-format, \"/tmp\", 1"), a.unwrap());
+```
+
+Every element can be the arguments of one iteration step with `~:{`, and `~:^` stops before the last one:
+
+```rust
+use cl_format::*;
+let a = vec![tilde!(&'a'), &1];
+let b = vec![tilde!(&'b'), &2];
+let l = vec![tilde!(&a), &b];
+let s = cl_format!("~:{~a: ~a~:^, ~}", &l);
+assert_eq!(String::from("a: 1, b: 2"), s.unwrap());
+```
+
+A prefix parameter caps the number of iterations, and the empty body `~{~}` takes its control string from the arguments:
+
+```rust
+use cl_format::*;
+let l = vec![tilde!(&1), &2, &3, &4];
+let a = cl_format!("~2{<~a>~}", &l);
+assert_eq!(String::from("<1><2>"), a.unwrap());
+
+let a = cl_format!("~{~}", &"~a;", &l);
+assert_eq!(String::from("1;2;3;4;"), a.unwrap());
+```
+
+A long control string can be continued on the next line with `~` and a newline, the newline and the indentation after it are ignored:
+
+```rust
+use cl_format::*;
+let l = vec![tilde!(&1), &2, &3];
+let a = cl_format!(
+    "~{~a~^, ~
+     ~}",
+    &l
+);
+assert_eq!(String::from("1, 2, 3"), a.unwrap());
+```
+
 Now, let's try some condition control (you can get the meaning of the condition control string in the `Conditional Formatting` chapter of [A Few FORMAT Recipes](https://gigamonkeys.com/book/a-few-format-recipes.html)):
 
 ```rust
@@ -95,6 +150,14 @@ let a = cl_format!("~{~a~#[~;, and ~:;, ~]~}", &l);
 assert_eq!(String::from("1, 2, 3, and 4"), a.unwrap());
 ```
 
+The parameter of directives can be `v` to take it from the next argument, or `#` for the number of arguments left. So the width can be decided at runtime without making a new control string:
+
+```rust
+use cl_format::*;
+let a = cl_format!("~v,'0d", &5, &42);
+assert_eq!(String::from("00042"), a.unwrap());
+```
+
 ### Manually ###
 
 Using macros will generate the control string instance every time. It might be wasteful if you are trying to use a control string everywhere because it is flexible enough for multiple uses.
@@ -139,6 +202,8 @@ let args = Args::new(vec![&list]);
 assert_eq!(cs.reveal(args).unwrap(), "1, 2, 3, and 4".to_string());
 ```
 
+The parsed `Tilde` and `TildeKind` come from the cl-format-parser crate and are re-exported here. Since they moved there, revealing one tilde is the `RevealTilde` trait rather than the inherent `Tilde::reveal` method, so bring it into scope with `use cl_format::RevealTilde` (`use cl_format::*` already does) to call `tilde.reveal(arg, &mut buf)`. It returns the `Reveal` telling if `~^` escaped instead of `()`.
+
 Let's try a mixed example: 
 
 ```rust
@@ -246,24 +311,66 @@ assert_eq!(
     "First: a: 1, b: b; Second: 1".to_string(),
     cl_format!("First: ~a; Second: ~d", &s, &s).unwrap()
 );
+
+// the padding of `~mincol,colinc,minpad,padcharA` is handled by cl_format
+assert_eq!("a: 1, b: b    |".to_string(), cl_format!("~14a|", &s).unwrap());
+```
+
+Or derive it. `#[tilde(...)]` picks the directives: `"Display"` and `"Debug"` reveal the type by `Display` and `Debug` (only for `va` of `~a` and `standard` of `~s`), and a field reveals the type by the same directive of the field.
+
+```rust
+use cl_format::*;
+
+#[derive(Debug, TildeAble)]
+#[tilde(va = "Display", standard = "Debug", digit = a)]
+struct MyStruct {
+    a: usize,
+    b: String,
+}
+
+impl std::fmt::Display for MyStruct {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "a: {}, b: {}", self.a, self.b)
+    }
+}
+
+let s = MyStruct {
+    a: 1234,
+    b: "b".to_string(),
+};
+
+assert_eq!(
+    "a: 1234, b: b; 1,234".to_string(),
+    cl_format!("~a; ~:d", &s, &s).unwrap()
+);
 ```
 
+The directives can be derived are `char`, `float`, `exponential`, `general`, `monetary`, `radix`, `digit`, `binary`, `octal`, `hex`, `va`, `standard`, `plural`, and `cond`.
+
 ## Format directives ##
 
 This is the table of which directives have been implemented:
 
-| tilde                     | rust type                                                                                    |
-|:-------------------------:|:--------------------------------------------------------------------------------------------:|
-| `~a`                      | f32, f64, char, i8, i16, i32, i64, i128, isize, bool, u8, u16, u32, u64, u128, usize, String |
-| `~s`                      | f32, f64, char, i32, i64, usize, bool, u32, u64, String                                      |
-| `~d`                      | i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize                               |
-| `~C`                      | char                                                                                         |
-| `~[~]` (normal condition) | bool, usize                                                                                  |
-| `~R`                      | i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize                               |
+| tilde                     | rust type                                                                                          |
+|:-------------------------:|:--------------------------------------------------------------------------------------------------:|
+| `~a`                      | f32, f64, char, i8, i16, i32, i64, i128, isize, bool, u8, u16, u32, u64, u128, usize, String, Disp |
+| `~s`                      | f32, f64, char, i32, i64, usize, bool, u32, u64, String, Dbg                                       |
+| `~d`                      | i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize                                     |
+| `~B`                      | i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize                                     |
+| `~O`                      | i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize                                     |
+| `~X`                      | i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize                                     |
+| `~F`                      | f32, f64, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize                           |
+| `~E`                      | f32, f64                                                                                           |
+| `~G`                      | f32, f64                                                                                           |
+| `~$`                      | f32, f64, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize                           |
+| `~C`                      | char                                                                                               |
+| `~[~]` (normal condition) | bool, usize                                                                                        |
+| `~R`                      | i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize                                     |
+| `~P`                      | i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize                                     |
+| `~?` `~@?`                | String, &str, ControlStr                                                                           |
 
 "#]
 #![feature(let_chains)]
-#![feature(pattern)]
 
 mod control_str;
 mod tildes;
@@ -271,6 +378,21 @@ mod tildes;
 pub use control_str::*;
 pub use tildes::*;
 
+#[doc(hidden)]
+pub use cl_format_macros::cl_format_literal;
+#[doc(hidden)]
+pub use cl_format_macros::control_str_literal;
+/// derive `TildeAble` of the custom type, like `#[derive(Debug, TildeAble)] #[tilde(va = "Display")]`
+pub use cl_format_macros::TildeAble;
+
+/// parse the control string literal at compile time, `control_str!("~a")` is the `&'static ControlStr<'static>`
+#[macro_export]
+macro_rules! control_str {
+    ($control_str:literal) => {
+        $crate::control_str_literal!($crate, $control_str)
+    };
+}
+
 #[doc = r#"Helper macro for implementing type with specific Tilde traits
 
 For example `multi_tilde_impl!(TildeKindVa, [float, char, String], self, {Err("un-implenmented yet".into())})`
@@ -283,9 +405,21 @@ impl TildeKindVa for float {
         Err("un-implenmented yet".into())
     }
 }
-```"#]
+```
+
+Give one more ident before the `buf` if the body needs the `tkind`:
+`multi_tilde_impl!(TildeKindFloat, [f32, f64], self, tkind, buf, {...})`"#]
 #[macro_export]
 macro_rules! multi_tilde_impl {
+    ($implName:ident, [$($y:ident),+], $s:ident, $tkind:ident, $buf:ident, $body:block) => {
+		$(
+			impl $implName for $y {
+				fn format(&$s, $tkind: &TildeKind, $buf: &mut String) -> Result<(), TildeError>
+					$body
+
+			}
+		)+
+    };
     ($implName:ident, [$($y:ident),+], $s:ident, $buf:ident, $body:block) => {
 		$(
 			impl $implName for $y {
@@ -297,11 +431,20 @@ macro_rules! multi_tilde_impl {
     };
 }
 
-#[doc = r"Macro for adding `as &dyn crate::TildeAble` to the expr"]
+#[doc = r#"Macro for adding `as &dyn crate::TildeAble` to the expr
+
+`tilde!(disp: &a)` and `tilde!(dbg: &a)` are `&Disp(&a)` and `&Dbg(&a)`, so any
+`Display` type can be revealed by `~a` and any `Debug` type can be revealed by `~s`."#]
 #[macro_export]
 macro_rules! tilde {
+    (disp: $arg:expr) => {
+        &$crate::Disp($arg) as &dyn $crate::TildeAble
+    };
+    (dbg: $arg:expr) => {
+        &$crate::Dbg($arg) as &dyn $crate::TildeAble
+    };
     ($arg:expr) => {
-        $arg as &dyn cl_format::TildeAble
+        $arg as &dyn $crate::TildeAble
     };
 }
 
@@ -320,6 +463,12 @@ cl_format!(control_str, &a, &b, &c) => {
         c.reveal(a)
 }
 ```
+
+The string literal control string is parsed at compile time instead, so the bad control string
+is a compile error rather than a panic. The number of args and the traits the args need for
+their tildes, like `TildeKindDigit` of `~d`, are checked at compile time too, except the
+`&dyn TildeAble` args, which are only checked when revealing.
+
 For example:
 
 ```rust
@@ -329,17 +478,20 @@ assert_eq!(String::from("1, 2, 3, and 4"), a.unwrap());
 ```"#]
 #[macro_export]
 macro_rules! cl_format {
+	($control_str:literal $(, $ele:expr)*) =>	{
+		$crate::cl_format_literal!($crate, $control_str $(, $ele)*)
+	};
 	($control_str:expr) =>	{
 		{
-			let c = cl_format::ControlStr::new($control_str).expect("making control string has issue");
-			let a = cl_format::Args::new(vec![]);
+			let c = $crate::ControlStr::new($control_str).expect("making control string has issue");
+			let a = $crate::Args::new(vec![]);
 			c.reveal(a)
 		}
 	};
     ($control_str:expr, $($ele:expr),*) =>	{
 		{
-			let c = cl_format::ControlStr::new($control_str).expect("making control string has issue");
-			let a = Into::<Args<'_,'_>>::into([$(tilde!($ele)),*]);
+			let c = $crate::ControlStr::new($control_str).expect("making control string has issue");
+			let a = Into::<$crate::Args<'_,'_>>::into([$($crate::tilde!($ele)),*]);
 			c.reveal(a)
 		}
 	}
@@ -1,13 +1,11 @@
 use crate::*;
 
+use cl_format_macros::TildeKindTraits;
 use std::cell::RefCell;
-use std::collections::VecDeque;
 use std::fmt::Debug;
-use std::io::{BufRead, Cursor, Read, Seek, SeekFrom};
 use std::ops::Deref;
 
-mod tilde_kinds;
-pub use tilde_kinds::*;
+pub use cl_format_parser::*;
 
 mod reveal_impl;
 pub use reveal_impl::*;
@@ -16,1470 +14,424 @@ mod args;
 pub use args::*;
 
 mod tilde_able_impl;
+pub use tilde_able_impl::*;
 
-#[doc = r"The tilde struct"]
-#[derive(Debug, PartialEq, Clone)]
-pub struct Tilde {
-    len: usize,
-    value: TildeKind,
+#[doc = "Reveal, how the tildes after the revealed one go on"]
+#[derive(Debug, PartialEq, Clone, Copy)]
+pub enum Reveal {
+    Continue,     // reveal the next tilde
+    Escape(bool), // ~^ escapes upward, true for ~:^ ending the whole ~:{ iteration
 }
 
-impl Tilde {
-    pub fn new(len: usize, value: TildeKind) -> Self {
-        Self { len, value }
-    }
-
-    pub fn len(&self) -> usize {
-        self.len
-    }
-
-    pub fn reveal(&self, arg: &dyn TildeAble, mut buf: &mut String) -> Result<(), TildeError> {
-        self.value.match_reveal(arg, &mut buf)?;
-        Ok(())
-    }
-
-    /*
-    ===============================
-    parse functions below
-    ===============================
-    */
-
-    /// start from '~' to the key char of tilde kind
-    fn scan_for_kind(
-        c: &mut Cursor<&'_ str>,
-    ) -> Result<
-        Box<dyn for<'a, 'b> Fn(&'a mut std::io::Cursor<&'b str>) -> Result<Tilde, TildeError>>,
-        TildeError,
-    > {
-        let mut buf = [0u8; 1];
-        c.read(&mut buf)
-            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-        if buf[0] != b'~' {
-            return Err(TildeError::new(ErrorKind::ParseError, "should start with ~").into());
-        }
-
-        let mut bucket = vec![b'~'];
-        let mut buf_offset = 1;
-        let mut escape = false;
-
-        loop {
-            if c.read(&mut buf)
-                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?
-                == 0
-                || buf[0] == 0_u8
-            {
-                return Err(TildeError::new(ErrorKind::ParseError, "read to end"));
-            }
+#[derive(Debug)]
+struct TildeNil;
+
+/// The directives of the `TildeKind` variants of the same names, `TildeKindTraits` generates
+/// the `TildeAble` trait and the `TildeKind*` trait of every directive from them.
+#[derive(Debug, TildeKindTraits)]
+#[allow(dead_code)]
+enum Directive {
+    #[implTo(char)]
+    Char,
+    #[implTo(
+        f32, f64, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize
+    )]
+    Float,
+    #[implTo(f32, f64)]
+    Exponential,
+    #[implTo(f32, f64)]
+    General,
+    #[implTo(
+        f32, f64, i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize
+    )]
+    Monetary,
+    #[implTo(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize)]
+    Radix,
+    #[implTo(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize)]
+    Digit,
+    #[implTo(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize)]
+    Binary,
+    #[implTo(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize)]
+    Octal,
+    #[implTo(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize)]
+    Hex,
+    #[implTo(
+        f32, f64, char, i8, i16, i32, i64, i128, isize, bool, u8, u16, u32, u64, u128, usize,
+        String, TildeNil
+    )]
+    Va,
+    Star,
+    #[implTo(f32, f64, char, i32, i64, usize, bool, u32, u64, String)]
+    Standard,
+    #[handwritten]
+    Loop,
+    /// the tilde with `V` or `#` parameters, it is not a `TildeKind` variant
+    #[implTo(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize, char)]
+    #[handwritten]
+    Params,
+    #[implTo(String)]
+    #[handwritten]
+    Recursive,
+    #[implTo(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize)]
+    Plural,
+    #[implTo(TildeNil)]
+    Justify,
+    #[implTo(TildeNil)]
+    #[reveal]
+    CaseConv,
+    #[implTo(TildeNil)]
+    #[reveal]
+    LoopEnd,
+    Tildes,
+    Newline,
+    FreshLine,
+    Page,
+    Tabulate,
+    #[implTo(usize, bool)]
+    #[handwritten]
+    Cond,
+    Text,
+    #[implTo(TildeNil)]
+    #[reveal]
+    VecTilde,
+}
 
-            buf_offset += 1;
-            bucket.extend_from_slice(&buf);
-            //dbg!(&bucket);
-            if !escape {
-                match &bucket[..] {
-                    [.., b'\\'] => {
-                        // escape the next one
-                        escape = true;
-                        continue;
-                    }
-                    [.., b'a'] | [.., b'A'] => {
-                        c.seek(SeekFrom::Current(-buf_offset))
-                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
-                        return Ok(Box::new(Self::parse_value));
-                    }
-                    [.., b'@', b'{'] | [.., b'{'] => {
-                        c.seek(SeekFrom::Current(-buf_offset))
-                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
-                        return Ok(Box::new(Self::parse_loop));
-                    }
-                    [.., _, b'$'] | [.., b'$'] | [.., b'f' | b'F'] => {
-                        c.seek(SeekFrom::Current(-buf_offset))
-                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
-                        return Ok(Box::new(Self::parse_float));
-                    }
-                    [.., b'd'] | [.., b'D'] => {
-                        c.seek(SeekFrom::Current(-buf_offset))
-                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
-                        return Ok(Box::new(Self::parse_digit));
-                    }
-                    [.., b'#' | b':' | b'@', b'['] | [.., b'['] => {
-                        c.seek(SeekFrom::Current(-buf_offset))
-                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
-                        return Ok(Box::new(Self::parse_cond));
-                    }
-                    [.., b'^'] => {
-                        c.seek(SeekFrom::Current(-buf_offset))
-                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
-                        return Ok(Box::new(Self::parse_loop_end));
-                    }
-                    [.., b':', b'*'] | [.., b'*'] => {
-                        c.seek(SeekFrom::Current(-buf_offset))
-                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
-                        return Ok(Box::new(Self::parse_star));
-                    }
-                    [.., _, b'~'] => {
-                        //:= can only parse the one digit number
-                        c.seek(SeekFrom::Current(-buf_offset))
-                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
-                        return Ok(Box::new(Self::parse_tildes));
-                    }
-                    [.., b'S'] | [.., b's'] => {
-                        c.seek(SeekFrom::Current(-buf_offset))
-                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
-                        return Ok(Box::new(Self::parse_standard));
-                    }
-                    [.., b'@', b'c' | b'C'] | [.., b'C' | b'c'] => {
-                        c.seek(SeekFrom::Current(-buf_offset))
-                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
-                        return Ok(Box::new(Self::parse_char));
-                    }
-                    [.., b'R' | b'r'] => {
-                        c.seek(SeekFrom::Current(-buf_offset))
-                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?; // back to start
-                        return Ok(Box::new(Self::parse_radix));
-                    }
+/// reveal the tildes parsed by cl-format-parser with the args
+///
+/// It replaces the inherent `Tilde::reveal` since `Tilde` is defined in cl-format-parser,
+/// `use cl_format::RevealTilde` to call it.
+pub trait RevealTilde {
+    fn reveal(&self, arg: &dyn TildeAble, buf: &mut String) -> Result<Reveal, TildeError>;
+}
 
-                    [.., b'%'] => {
-                        return Err(TildeError::new(
-                            ErrorKind::ParseError,
-                            "Unsupport the last symbol of '%', try to use \\n instead",
-                        ));
-                    }
-                    _ => {}
-                }
-            }
-            escape = false;
+impl RevealTilde for Tilde {
+    fn reveal(&self, arg: &dyn TildeAble, buf: &mut String) -> Result<Reveal, TildeError> {
+        if self.params().is_empty() {
+            return self.value().reveal(arg, buf);
         }
-    }
 
-    /// cursor should located on '~'
-    pub fn parse(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
-        //dbg!(&c);
-        let parser = Self::scan_for_kind(c)?;
-        parser(c)
+        // the `V` and `#` parameters are resolved against the args first
+        TildeKindParams::format(arg, self, buf)
     }
+}
 
-    /// parse function for '~{~}'
-    fn parse_loop(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
-        let mut char_buf = [0u8; 3]; // three bytes
-        c.read(&mut char_buf)
-            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-
-        let mut loop_kind = TildeLoopKind::Nil;
-        let mut total_len = 0;
-        match char_buf {
-            [b'~', b'{', ..] => {
-                total_len += 2;
-                c.seek(SeekFrom::Current(-1))
-                    .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-            }
-            [b'~', b'@', b'{'] => {
-                total_len += 3;
-                loop_kind = TildeLoopKind::At;
-            }
-
-            _ => {
-                c.seek(SeekFrom::Current(-3))
-                    .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-                return Err(TildeError::new(
-                    ErrorKind::ParseError,
-                    "should start with ~{",
-                ));
+impl RevealTilde for TildeKind {
+    fn reveal(&self, arg: &dyn TildeAble, buf: &mut String) -> Result<Reveal, TildeError> {
+        match self {
+            TildeKind::Char(_) => {
+                let a = arg
+                    .into_tildekind_char()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to Char",
+                    ))?;
+
+                a.format(self, buf)
             }
-        }
-
-        let mut result = vec![];
-        let mut buf = vec![];
-        let mut char_buf = [0u8; 3];
-
-        loop {
-            // read text until the next '~'
-            c.read_until(b'~', &mut buf)
-                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-
-            match buf.as_slice() {
-                [b'~'] => {
-                    c.seek(SeekFrom::Current(-1))
-                        .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-                }
-                [.., b'~'] => {
-                    c.seek(SeekFrom::Current(-1))
-                        .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-                    result.push(Tilde::new(
-                        buf.len() - 1,
-                        TildeKind::Text(
-                            String::from_utf8(buf[..buf.len() - 1].to_vec()).map_err(|e| {
-                                TildeError::new(ErrorKind::ParseError, e.to_string())
-                            })?,
-                        ),
-                    ));
-                    total_len += buf.len() - 1;
-                }
-                [..] => {
-                    result.push(Tilde::new(
-                        buf.len() - 1,
-                        TildeKind::Text(
-                            String::from_utf8(buf[..buf.len() - 1].to_vec()).map_err(|e| {
-                                TildeError::new(ErrorKind::ParseError, e.to_string())
-                            })?,
-                        ),
-                    ));
-                    total_len += buf.len();
-                    return Ok(Tilde::new(total_len, TildeKind::Loop((result, loop_kind))));
-                }
+            TildeKind::Float(_) => {
+                let a = arg
+                    .into_tildekind_float()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to Float",
+                    ))?;
+
+                a.format(self, buf)
             }
-
-            c.read(&mut char_buf)
-                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-
-            match char_buf {
-                [b'~', b'}', 0] => {
-                    return Ok(Tilde::new(
-                        total_len + 2,
-                        TildeKind::Loop((result, loop_kind)),
-                    ));
-                }
-                [b'~', b'}', ..] => {
-                    c.seek(SeekFrom::Current(-1))
-                        .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-                    return Ok(Tilde::new(
-                        total_len + 2,
-                        TildeKind::Loop((result, loop_kind)),
-                    ));
-                }
-                [b'~', b':', b'}'] => {
-                    return Ok(Tilde::new(
-                        total_len + 3,
-                        TildeKind::Loop((
-                            result,
-                            if loop_kind == TildeLoopKind::Nil {
-                                TildeLoopKind::NilColon
-                            } else {
-                                loop_kind
-                            },
-                        )),
-                    ));
-                }
-                _ => {
-                    let back = 3 - char_buf.iter().filter(|b| **b == 0).count() as i64;
-                    c.seek(SeekFrom::Current(-back))
-                        .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-                }
+            TildeKind::Exponential(_) => {
+                let a = arg
+                    .into_tildekind_exponential()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to Exponential",
+                    ))?;
+
+                a.format(self, buf)
             }
-
-            // read the tilde
-            let next = Tilde::parse(c)?;
-            total_len += next.len;
-            result.push(next);
-            char_buf = [0; 3];
-            buf.clear()
-        }
-    }
-
-    /// parse the ~^ in loop
-    fn parse_loop_end(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
-        let mut char_buf = [0u8; 2]; // three bytes
-        c.read(&mut char_buf)
-            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-        if char_buf != *b"~^" {
-            c.seek(SeekFrom::Current(-2))
-                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-            return Err(TildeError::new(
-                ErrorKind::ParseError,
-                "should start with ~^",
-            ));
-        }
-
-        Ok(Tilde {
-            len: 2,
-            value: TildeKind::LoopEnd,
-        })
-    }
-
-    /// parse the '~[~]'
-    fn parse_cond(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
-        let mut char_buf = [0u8; 3]; // three bytes
-        let mut total_len = 0;
-        let mut cond_kind;
-        c.read(&mut char_buf)
-            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-
-        match char_buf {
-            [b'~', b'[', ..] => {
-                total_len += 2;
-                cond_kind = TildeCondKind::Nil(false);
-                c.seek(SeekFrom::Current(-1))
-                    .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
+            TildeKind::General(_) => {
+                let a = arg
+                    .into_tildekind_general()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to General",
+                    ))?;
+
+                a.format(self, buf)
             }
-            [b'~', b'#', b'['] => {
-                total_len += 3;
-                cond_kind = TildeCondKind::Sharp;
+            TildeKind::Monetary(_) => {
+                let a = arg
+                    .into_tildekind_monetary()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to Monetary",
+                    ))?;
+
+                a.format(self, buf)
             }
-            [b'~', b'@', b'['] => {
-                total_len += 3;
-                cond_kind = TildeCondKind::At;
+            TildeKind::Digit(_) => {
+                let a = arg
+                    .into_tildekind_digit()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to Digit",
+                    ))?;
+
+                a.format(self, buf)
             }
-            [b'~', b':', b'['] => {
-                total_len += 3;
-                cond_kind = TildeCondKind::Colon;
+            TildeKind::Binary(_) => {
+                let a = arg
+                    .into_tildekind_binary()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to Binary",
+                    ))?;
+
+                a.format(self, buf)
             }
-            _ => {
-                c.seek(SeekFrom::Current(-3))
-                    .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-                return Err(TildeError::new(
-                    ErrorKind::ParseError,
-                    "should start with ~[, ~#[, ~@[",
-                ));
+            TildeKind::Octal(_) => {
+                let a = arg
+                    .into_tildekind_octal()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to Octal",
+                    ))?;
+
+                a.format(self, buf)
             }
-        }
-
-        let mut buffer = vec![];
-        let mut one_byte = [0_u8; 1];
-
-        let mut cache = vec![];
-        let mut result: Vec<Tilde> = vec![];
-
-        loop {
-            c.read_exact(&mut one_byte)
-                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-            if one_byte == [0] {
-                break;
+            TildeKind::Hex(_) => {
+                let a = arg
+                    .into_tildekind_hex()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to Hex",
+                    ))?;
+
+                a.format(self, buf)
             }
-
-            if one_byte == [b'~'] {
-                if !buffer.is_empty() {
-                    cache.push(Tilde {
-                        len: buffer.len(),
-                        value: TildeKind::Text(
-                            String::from_utf8(buffer.clone()).map_err(|e| {
-                                TildeError::new(ErrorKind::ParseError, e.to_string())
-                            })?,
-                        ),
-                    });
-                    buffer.clear();
-                }
-
-                one_byte[0] = 0;
-                c.read_exact(&mut one_byte)
-                    .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-                match one_byte {
-                    [b':'] => {
-                        one_byte[0] = 0;
-                        c.read_exact(&mut one_byte)
-                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-                        if one_byte == [b';'] {
-                            let cache_len = cache.iter().map(|t: &Tilde| t.len()).sum::<usize>();
-                            result.push(Tilde::new(cache_len, TildeKind::VecTilde(cache.clone())));
-                            cond_kind.to_true();
-                            cache.clear();
-                            total_len += 3 + cache_len;
-                        } else {
-                            panic!()
-                        }
-                    }
-                    [b';'] => {
-                        let cache_len = cache.iter().map(|t: &Tilde| t.len()).sum::<usize>();
-                        result.push(Tilde::new(cache_len, TildeKind::VecTilde(cache.clone())));
-                        cache.clear();
-                        total_len += 2 + cache_len;
-                    }
-                    [b']'] => {
-                        let cache_len = cache.iter().map(|t: &Tilde| t.len()).sum::<usize>();
-                        result.push(Tilde::new(cache_len, TildeKind::VecTilde(cache.clone())));
-                        total_len += 2 + cache_len;
-                        break;
-                    }
-                    _ => {
-                        c.seek(SeekFrom::Current(-2))
-                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-                        let c = Self::parse(c)?;
-                        cache.push(c);
+            TildeKind::Va(padding) => {
+                let a = arg
+                    .into_tildekind_va()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to Va",
+                    ))?;
+
+                match padding {
+                    Some(padding) => {
+                        let start = buf.len();
+                        a.format(self, buf)?;
+                        pad_column(buf, start, padding);
+                        Ok(())
                     }
+                    None => a.format(self, buf),
                 }
-                one_byte[0] = 0;
-            } else {
-                buffer.push(one_byte[0]);
             }
-        }
-
-        Ok(Tilde::new(total_len, TildeKind::Cond((result, cond_kind))))
-    }
-
-    #[cfg(test)]
-    fn parse_vec(
-        c: &mut Cursor<&'_ str>,
-        end_len: usize,
-    ) -> Result<Self, Box<dyn std::error::Error>> {
-        let mut bucket = vec![0; end_len].into_boxed_slice();
-        c.read_exact(&mut bucket)?;
-
-        let ss = String::from_utf8(bucket.as_ref().to_vec())?;
-        //dbg!(&ss);
-        let mut inner_c = Cursor::new(ss.as_str());
-        let mut buf = vec![];
-        let mut result = vec![];
-        let mut total_len = 0;
-
-        loop {
-            // read text until the next '~'
-            inner_c.read_until(b'~', &mut buf)?;
-
-            match buf.as_slice() {
-                [b'~'] => {
-                    inner_c.seek(SeekFrom::Current(-1))?;
-                }
-                [.., b'~'] => {
-                    inner_c.seek(SeekFrom::Current(-1))?;
-                    result.push(Tilde::new(
-                        buf.len() - 1,
-                        TildeKind::Text(String::from_utf8(buf[..buf.len() - 1].to_vec())?),
-                    ));
-                    total_len += buf.len() - 1;
-                }
-                [] => {
-                    c.seek(SeekFrom::Current((end_len - total_len) as i64 * -1))?;
-                    return Ok(Tilde::new(total_len, TildeKind::VecTilde(result)));
-                }
-                [..] => {
-                    result.push(Tilde::new(
-                        buf.len(),
-                        TildeKind::Text(String::from_utf8(buf[..buf.len()].to_vec())?),
-                    ));
-                    total_len += buf.len();
-                    c.seek(SeekFrom::Current((end_len - total_len) as i64 * -1))?;
-                    return Ok(Tilde::new(total_len, TildeKind::VecTilde(result)));
-                }
+            TildeKind::Loop(_) => {
+                let a = arg
+                    .into_tildekind_loop()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to Loop",
+                    ))?;
+
+                a.format(self, buf)
             }
-
-            let next = Tilde::parse(&mut inner_c)?;
-            total_len += next.len;
-            result.push(next);
-
-            buf.clear()
-        }
-    }
-
-    /// parse function for '~a'
-    fn parse_value(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
-        let mut buf = vec![];
-        //:= this for in maybe re-write in helper function
-        for t in [b'a', b'A'] {
-            c.read_until(t, &mut buf)
-                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-            match buf.last() {
-                Some(b) if *b == t => return Ok(Tilde::new(buf.len(), TildeKind::Va)),
-                _ => (),
+            TildeKind::Plural(_) => {
+                let a = arg
+                    .into_tildekind_plural()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to Plural",
+                    ))?;
+
+                a.format(self, buf)
             }
-            c.seek(SeekFrom::Current(-(buf.len() as i64)))
-                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-            buf.clear();
-        }
-        Err(TildeError::new(
-            ErrorKind::ParseError,
-            "should start with ~a",
-        ))
-    }
-
-    /// parse the float
-    fn parse_float(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
-        let mut buf = vec![];
-
-        for t in [b'$', b'f', b'F'] {
-            c.read_until(t, &mut buf)
-                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-            match buf.last() {
-                Some(b) if *b == t => {
-                    return Ok(Tilde::new(
-                        buf.len(),
-                        TildeKind::Float(Some(
-                            String::from_utf8(
-                                buf.get(1..buf.len() - 1).map_or(Vec::new(), |s| s.to_vec()),
-                            )
-                            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?,
-                        )),
-                    ))
-                }
-                _ => (),
+            TildeKind::Justify(_) => {
+                let a = arg
+                    .into_tildekind_justify()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to Justify",
+                    ))?;
+
+                a.format(self, buf)
             }
-            c.seek(SeekFrom::Current(-(buf.len() as i64)))
-                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-            buf.clear();
-        }
-        Err(TildeError::new(
-            ErrorKind::ParseError,
-            "cannot find the '$' or 'f'",
-        ))
-    }
-
-    /// parse the digit
-    fn parse_digit(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
-        let mut buf = vec![];
-
-        for t in [b'd', b'D'] {
-            c.read_until(t, &mut buf)
-                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-            match buf.last() {
-                Some(b) if *b == t => {
-                    let s = String::from_utf8(
-                        buf.get(1..buf.len() - 1).map_or(Vec::new(), |s| s.to_vec()),
-                    )
-                    .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-                    return Ok(Tilde::new(
-                        buf.len(),
-                        if &s == "" {
-                            TildeKind::Digit(None)
-                        } else {
-                            TildeKind::Digit(Some(s))
-                        },
-                    ));
-                }
-                _ => (),
-            }
-            c.seek(SeekFrom::Current(-(buf.len() as i64)))
-                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-            buf.clear();
-        }
-        Err(TildeError::new(
-            ErrorKind::ParseError,
-            "cannot find the 'd' or 'D'",
-        ))
-    }
-
-    /// parse the star
-    fn parse_star(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
-        let mut char_buf = [0u8; 3]; // three bytes
-        c.read(&mut char_buf)
-            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-
-        match char_buf {
-            [b'~', b':', b'*'] => Ok(Self {
-                len: 3,
-                value: TildeKind::Star(StarKind::Hop),
-            }),
-            [b'~', b'*', ..] => {
-                c.seek(SeekFrom::Current(-1))
-                    .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-                Ok(Self {
-                    len: 2,
-                    value: TildeKind::Star(StarKind::Skip),
-                })
+            TildeKind::CaseConv(_) => {
+                let a = arg
+                    .into_tildekind_caseconv()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to CaseConv",
+                    ))?;
+
+                return a.format(self, buf);
             }
-            _ => {
-                c.seek(SeekFrom::Current(-3))
-                    .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-                return Err(TildeError::new(
-                    ErrorKind::ParseError,
-                    "should start with ~* or ~:*",
-                ));
+            TildeKind::LoopEnd(_) => {
+                let a = arg
+                    .into_tildekind_loopend()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to LoopEnd",
+                    ))?;
+                return a.format(self, buf);
             }
-        }
-    }
-
-    fn parse_tildes(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
-        let mut char_buf = [0u8; 3]; // three bytes
-        c.read(&mut char_buf)
-            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-        match char_buf {
-            [b'~', n @ _, b'~'] => Ok(Self {
-                len: 3,
-                value: TildeKind::Tildes(
-                    String::from_utf8(vec![n])
-                        .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?
-                        .parse::<usize>()
-                        .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?,
-                ),
-            }),
-            _ => {
-                c.seek(SeekFrom::Current(-3))
-                    .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-                return Err(TildeError::new(
-                    ErrorKind::ParseError,
-                    "should start with ~n~",
-                ));
+            TildeKind::Tildes(n) => {
+                buf.extend(std::iter::repeat_n('~', *n));
+                Ok(())
             }
-        }
-    }
-
-    fn parse_standard(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
-        let mut buf = vec![];
-        let mut one_byte = [0; 1];
-        loop {
-            if c.read(&mut one_byte)
-                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?
-                == 0
-            {
-                c.seek(SeekFrom::Current(-(buf.len() as i64)))
-                    .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-                return Err(TildeError::new(
-                    ErrorKind::ParseError,
-                    "cannot find the 's' or 'S'",
-                ));
+            TildeKind::Newline(n) => {
+                buf.extend(std::iter::repeat_n('\n', *n));
+                Ok(())
             }
-
-            match one_byte[0] {
-                b's' | b'S' => return Ok(Tilde::new(buf.len() + 1, TildeKind::Standard)),
-                _ => buf.extend_from_slice(&one_byte),
+            TildeKind::FreshLine(n) => {
+                let n = if buf.is_empty() || buf.ends_with('\n') {
+                    n.saturating_sub(1)
+                } else {
+                    *n
+                };
+                buf.extend(std::iter::repeat_n('\n', n));
+                Ok(())
             }
-        }
-    }
-
-    fn parse_char(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
-        let mut char_buf = [0u8; 3]; // three bytes
-        c.read(&mut char_buf)
-            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-
-        match char_buf {
-            [b'~', b'@', b'c' | b'C'] => Ok(Self {
-                len: 3,
-                value: TildeKind::Char(CharKind::At),
-            }),
-            [b'~', b'c' | b'C', ..] => {
-                c.seek(SeekFrom::Current(-1))
-                    .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-                Ok(Self {
-                    len: 2,
-                    value: TildeKind::Char(CharKind::Nil),
-                })
+            TildeKind::Page(n) => {
+                buf.extend(std::iter::repeat_n('\u{c}', *n));
+                Ok(())
             }
-            _ => {
-                c.seek(SeekFrom::Current(-3))
-                    .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-                return Err(TildeError::new(
-                    ErrorKind::ParseError,
-                    "should start with ~c or ~@c",
-                ));
+            TildeKind::Tabulate(params) => {
+                tabulate(buf, params);
+                Ok(())
             }
-        }
-    }
-
-    fn parse_radix(c: &mut Cursor<&'_ str>) -> Result<Self, TildeError> {
-        let mut buf = [0u8; 1];
-        let mut bucket = vec![];
-        loop {
-            if c.read(&mut buf)
-                .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?
-                == 0
-                || buf[0] == 0_u8
-            {
-                return Err(TildeError::new(ErrorKind::ParseError, "read to end"));
+            TildeKind::Text(s) => {
+                buf.push_str(s);
+                Ok(())
             }
-
-            bucket.extend_from_slice(&buf);
-            if buf[0] == b'R' || buf[0] == b'r' {
-                break;
+            TildeKind::VecTilde(_) => {
+                let a = arg
+                    .into_tildekind_vectilde()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to VecTilde",
+                    ))?;
+
+                return a.format(self, buf);
             }
-
-            buf[0] = 0u8;
-        }
-
-        let whole = String::from_utf8(bucket[1..].to_vec())
-            .map_err(|e| TildeError::new(ErrorKind::ParseError, e.to_string()))?;
-
-        let mut splited_bucket = whole.split(',').collect::<VecDeque<_>>();
-
-        //dbg!(&splited_bucket);
-
-        // helper function for parsing the usize
-        let parse_usize_helper = |a: &str| -> Result<Option<usize>, TildeError> {
-            if a.len() == 0 {
-                Ok(None)
-            } else {
-                Ok(Some(a.parse::<usize>().map_err(|e| {
-                    TildeError::new(ErrorKind::ParseError, e.to_string())
-                })?))
+            TildeKind::Recursive(_) => {
+                let a = arg
+                    .into_tildekind_recursive()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to Recursive",
+                    ))?;
+                a.format(self, buf)
             }
-        };
-
-        let parse_u8_helper = |a: &str| -> Result<Option<u8>, TildeError> {
-            if a.len() == 0 {
-                Ok(None)
-            } else {
-                Ok(Some(a.parse::<u8>().map_err(|e| {
-                    TildeError::new(ErrorKind::ParseError, e.to_string())
-                })?))
+            TildeKind::Cond((_, _)) => {
+                let a = arg
+                    .into_tildekind_cond()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to Cond",
+                    ))?;
+                return a.format(self, buf);
             }
-        };
-
-        // helper function for parsing the char
-        let parse_char_helper = |a: &str| -> Result<Option<char>, TildeError> {
-            if a.len() == 0 {
-                Ok(None)
-            } else {
-                Ok(a.chars().last())
+            TildeKind::Star(_) => {
+                let a = arg
+                    .into_tildekind_star()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to Star",
+                    ))?;
+                a.format(self, buf)
             }
-        };
-
-        // helper function for parsing the radix flag
-        let parse_radix_flag = |a: &str| -> Result<(Option<usize>, Option<RadixFlag>), TildeError> {
-            match &a.split(':').collect::<Vec<_>>()[..] {
-                ["", "R"] => Ok((None, Some(RadixFlag::Colon))),
-                ["@R"] => Ok((None, Some(RadixFlag::At))),
-                ["", "@R"] => Ok((None, Some(RadixFlag::AtColon))),
-                ["R"] => Ok((None, None)),
-                [n @ _, "R"] => Ok((parse_usize_helper(n)?, Some(RadixFlag::Colon))),
-                [n @ _] if n.ends_with("R") => Ok((parse_usize_helper(&n[0..n.len() - 1])?, None)),
-                _ => Err(TildeError::new(
-                    ErrorKind::ParseError,
-                    format!("Parsing the radix flag has problem: {}", a),
-                )),
+            TildeKind::Standard(padding) => {
+                let a = arg
+                    .into_tildekind_standard()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to Standard",
+                    ))?;
+
+                match padding {
+                    Some(padding) => {
+                        let start = buf.len();
+                        a.format(self, buf)?;
+                        pad_column(buf, start, padding);
+                        Ok(())
+                    }
+                    None => a.format(self, buf),
+                }
             }
-        };
-
-        let flag;
-        let radix;
-        let mut mincol = None;
-        let mut padchar = None;
-        let mut commachar = None;
-        let mut comma_interval = None;
-
-        // has to be 1 or 5
-        // if there is no comma
-        if splited_bucket.len() == 1 {
-            let ra = splited_bucket.pop_front().unwrap();
-            let pair = parse_radix_flag(&ra)?;
-            (radix, flag) = (pair.0.map(|x| x as u8), pair.1)
-        } else if splited_bucket.len() == 5 {
-            radix = parse_u8_helper(splited_bucket.pop_front().unwrap())?;
-            mincol = parse_usize_helper(splited_bucket.pop_front().unwrap())?;
-            padchar = parse_char_helper(splited_bucket.pop_front().unwrap())?;
-            commachar = parse_char_helper(splited_bucket.pop_front().unwrap())?;
-
-            let ra = splited_bucket.pop_front().unwrap();
-            (comma_interval, flag) = parse_radix_flag(&ra)?;
-        } else {
-            return Err(TildeError::new(
-                ErrorKind::ParseError,
-                "The number of radix interval spaces should be 1 or 5",
-            ));
-        }
-
-        Ok(Self::new(
-            bucket.len(),
-            TildeKind::Radix((radix, mincol, padchar, commachar, comma_interval, flag)),
-        ))
+            TildeKind::Radix(_) => {
+                let a = arg
+                    .into_tildekind_radix()
+                    .ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::RevealError,
+                        "cannot reveal to Radix",
+                    ))?;
+                a.format(self, buf)
+            } //_ => unimplemented!(),
+        }?;
+
+        Ok(Reveal::Continue)
     }
-
-    // more parsers functions below
 }
 
-#[cfg(test)]
-mod tests {
-    use super::*;
-
-    #[test]
-    fn test_cursor() {
-        let mut testcase0 = Cursor::new("abcd");
-        assert_eq!(testcase0.position(), 0);
-
-        let mut buffer: [u8; 1] = [0; 1];
-        testcase0.set_position(2);
-        let _ = testcase0.read(&mut buffer);
-        assert_eq!(buffer[0], b'c');
-    }
-
-    #[test]
-    fn test_parse_va() -> Result<(), Box<dyn std::error::Error>> {
-        let mut case = Cursor::new("~a");
-        assert_eq!(Tilde::parse_value(&mut case)?, Tilde::new(2, TildeKind::Va));
-
-        let mut case = Cursor::new("~A");
-        assert_eq!(Tilde::parse_value(&mut case)?, Tilde::new(2, TildeKind::Va));
-        Ok(())
+/// the column padding of `~mincol,colinc,minpad,padcharA` and `~S`.
+///
+/// At least minpad padchars are added, then colinc padchars at a time until the length is at least mincol.
+/// The padding is on the right, or on the left with `@`. The text is `buf[start..]`, it is revealed
+/// in buf already so the tildes inside see the real column.
+fn pad_column(
+    buf: &mut String,
+    start: usize,
+    (mincol, colinc, minpad, padchar, left): &(usize, usize, usize, char, bool),
+) {
+    let len = buf[start..].chars().count() + minpad;
+    let mut pad = *minpad;
+    if len < *mincol {
+        let colinc = (*colinc).max(1);
+        pad += (mincol - len).div_ceil(colinc) * colinc;
     }
 
-    #[test]
-    fn test_parse_loop() -> Result<(), Box<dyn std::error::Error>> {
-        let mut case = Cursor::new("~{~}");
-
-        assert_eq!(
-            Tilde::parse_loop(&mut case)?,
-            Tilde::new(4, TildeKind::Loop((Vec::new(), TildeLoopKind::Nil)))
-        );
-
-        let mut case = Cursor::new("~{a bc~}");
-
-        assert_eq!(
-            Tilde::parse_loop(&mut case)?,
-            Tilde::new(
-                8,
-                TildeKind::Loop((
-                    vec![Tilde {
-                        len: 4,
-                        value: TildeKind::Text(String::from("a bc"))
-                    }],
-                    TildeLoopKind::Nil
-                ))
-            ),
-        );
-
-        let mut case = Cursor::new("~{a bc~a~}");
-
-        assert_eq!(
-            Tilde::parse_loop(&mut case)?,
-            Tilde::new(
-                10,
-                TildeKind::Loop((
-                    vec![
-                        Tilde {
-                            len: 4,
-                            value: TildeKind::Text(String::from("a bc"))
-                        },
-                        Tilde {
-                            len: 2,
-                            value: TildeKind::Va,
-                        }
-                    ],
-                    TildeLoopKind::Nil
-                ))
-            )
-        );
-
-        let mut case = Cursor::new("~{~aa bc~a~}");
-
-        assert_eq!(
-            Tilde::parse_loop(&mut case)?,
-            Tilde::new(
-                12,
-                TildeKind::Loop((
-                    vec![
-                        Tilde {
-                            len: 2,
-                            value: TildeKind::Va,
-                        },
-                        Tilde {
-                            len: 4,
-                            value: TildeKind::Text(String::from("a bc"))
-                        },
-                        Tilde {
-                            len: 2,
-                            value: TildeKind::Va,
-                        }
-                    ],
-                    TildeLoopKind::Nil
-                ))
-            )
-        );
-
-        let mut case = Cursor::new("~@{~a~}");
-
-        assert_eq!(
-            Tilde::parse_loop(&mut case)?,
-            Tilde::new(
-                7,
-                TildeKind::Loop((
-                    vec![Tilde {
-                        len: 2,
-                        value: TildeKind::Va,
-                    },],
-                    TildeLoopKind::At
-                ))
-            )
-        );
-
-        let mut case = Cursor::new("~@{~a~^, ~}");
-
-        assert_eq!(
-            Tilde::parse_loop(&mut case)?,
-            Tilde::new(
-                11,
-                TildeKind::Loop((
-                    vec![
-                        Tilde {
-                            len: 2,
-                            value: TildeKind::Va,
-                        },
-                        Tilde {
-                            len: 2,
-                            value: TildeKind::LoopEnd,
-                        },
-                        Tilde {
-                            len: 2,
-                            value: TildeKind::Text(", ".to_string()),
-                        }
-                    ],
-                    TildeLoopKind::At
-                ))
-            )
-        );
-
-        let mut case = Cursor::new("~{~a~:}");
-        assert_eq!(
-            Tilde::parse_loop(&mut case)?,
-            Tilde::new(
-                7,
-                TildeKind::Loop((
-                    vec![Tilde {
-                        len: 2,
-                        value: TildeKind::Va,
-                    },],
-                    TildeLoopKind::NilColon
-                ))
-            )
+    if *left {
+        buf.insert_str(
+            start,
+            &String::from_iter(std::iter::repeat_n(*padchar, pad)),
         );
-
-        Ok(())
+    } else {
+        buf.extend(std::iter::repeat_n(*padchar, pad));
     }
+}
 
-    #[test]
-    fn test_parse_float() -> Result<(), Box<dyn std::error::Error>> {
-        let mut case = Cursor::new("~$");
-        assert_eq!(
-            Tilde::parse_float(&mut case)?,
-            Tilde::new(2, TildeKind::Float(Some(String::new())))
-        );
-
-        let mut case = Cursor::new("~5$");
-        assert_eq!(
-            Tilde::parse_float(&mut case)?,
-            Tilde::new(3, TildeKind::Float(Some("5".to_string())))
-        );
-
-        let mut case = Cursor::new("~,5f");
-        assert_eq!(
-            Tilde::parse_float(&mut case)?,
-            Tilde::new(4, TildeKind::Float(Some(",5".to_string())))
-        );
-
-        Ok(())
-    }
-
-    #[test]
-    fn test_scan_for_kind() -> Result<(), Box<dyn std::error::Error>> {
-        let case = "~a";
-        let mut c = Cursor::new(case);
-        let f = Tilde::scan_for_kind(&mut c)?;
-
-        // let mut ss = String::new();
-        // c.read_to_string(&mut ss);
-        // dbg!(ss);
-        // c.seek(SeekFrom::Start(0));
-        //dbg!(f(&mut c));
-
-        assert_eq!(Tilde::new(2, TildeKind::Va), f(&mut c)?);
-        Ok(())
-    }
-
-    #[test]
-    fn test_parse_vec() -> Result<(), Box<dyn std::error::Error>> {
-        let mut case = Cursor::new("~a and ~a~a~a");
-        assert_eq!(
-            Tilde::parse_vec(&mut case, 9)?,
-            Tilde::new(
-                9,
-                TildeKind::VecTilde(vec![
-                    Tilde {
-                        len: 2,
-                        value: TildeKind::Va
-                    },
-                    Tilde {
-                        len: 5,
-                        value: TildeKind::Text(String::from(" and "))
-                    },
-                    Tilde {
-                        len: 2,
-                        value: TildeKind::Va
-                    }
-                ])
-            )
-        );
-
-        //dbg!(&case.position());
-        let mut rest = vec![];
-        case.read_to_end(&mut rest)?;
-        assert_eq!(String::from_utf8(rest)?, "~a~a");
-
-        //
-        let mut case = Cursor::new("~a a");
-        assert!(Tilde::parse_vec(&mut case, 9).is_err());
-
-        //
-        let mut case = Cursor::new("a");
-        assert_eq!(
-            Tilde::parse_vec(&mut case, 1)?,
-            Tilde::new(
-                1,
-                TildeKind::VecTilde(vec![Tilde {
-                    len: 1,
-                    value: TildeKind::Text(String::from("a"))
-                }])
-            )
-        );
-
-        Ok(())
-    }
-
-    #[test]
-    fn test_parse_cond() -> Result<(), Box<dyn std::error::Error>> {
-        let mut case = Cursor::new("~[~]");
-
-        assert_eq!(
-            Tilde::parse_cond(&mut case)?,
-            Tilde::new(
-                4,
-                TildeKind::Cond((
-                    vec![Tilde {
-                        len: 0,
-                        value: TildeKind::VecTilde(vec![])
-                    }],
-                    TildeCondKind::Nil(false)
-                ))
-            )
-        );
-
-        let mut case = Cursor::new("~[cero~]");
-
-        assert_eq!(
-            Tilde::parse_cond(&mut case)?,
-            Tilde::new(
-                8,
-                TildeKind::Cond((
-                    vec![Tilde {
-                        len: 4,
-                        value: TildeKind::VecTilde(vec![Tilde::new(
-                            4,
-                            TildeKind::Text(String::from("cero"))
-                        )]),
-                    }],
-                    TildeCondKind::Nil(false)
-                ))
-            ),
-        );
-
-        let mut case = Cursor::new("~[cero~;uno~;dos~]");
-
-        assert_eq!(
-            Tilde::parse_cond(&mut case)?,
-            Tilde::new(
-                18,
-                TildeKind::Cond((
-                    vec![
-                        Tilde {
-                            len: 4,
-                            value: TildeKind::VecTilde(vec![Tilde::new(
-                                4,
-                                TildeKind::Text(String::from("cero"))
-                            )])
-                        },
-                        Tilde {
-                            len: 3,
-                            value: TildeKind::VecTilde(vec![Tilde::new(
-                                3,
-                                TildeKind::Text(String::from("uno"))
-                            )])
-                        },
-                        Tilde {
-                            len: 3,
-                            value: TildeKind::VecTilde(vec![Tilde::new(
-                                3,
-                                TildeKind::Text(String::from("dos"))
-                            )])
-                        },
-                    ],
-                    TildeCondKind::Nil(false)
-                ))
-            )
-        );
-
-        let mut case = Cursor::new("~[cero~;uno~;~]");
-
-        assert_eq!(
-            Tilde::parse_cond(&mut case)?,
-            Tilde::new(
-                15,
-                TildeKind::Cond((
-                    vec![
-                        Tilde {
-                            len: 4,
-                            value: TildeKind::VecTilde(vec![Tilde::new(
-                                4,
-                                TildeKind::Text(String::from("cero"))
-                            )])
-                        },
-                        Tilde {
-                            len: 3,
-                            value: TildeKind::VecTilde(vec![Tilde::new(
-                                3,
-                                TildeKind::Text(String::from("uno"))
-                            )])
-                        },
-                        Tilde {
-                            len: 0,
-                            value: TildeKind::VecTilde(vec![])
-                        },
-                    ],
-                    TildeCondKind::Nil(false)
-                ))
-            )
-        );
-
-        let mut case = Cursor::new("~:[cero~;uno~]");
-        assert_eq!(
-            Tilde::parse_cond(&mut case)?,
-            Tilde::new(
-                14,
-                TildeKind::Cond((
-                    vec![
-                        Tilde {
-                            len: 4,
-                            value: TildeKind::VecTilde(vec![Tilde::new(
-                                4,
-                                TildeKind::Text(String::from("cero"))
-                            )])
-                        },
-                        Tilde {
-                            len: 3,
-                            value: TildeKind::VecTilde(vec![Tilde::new(
-                                3,
-                                TildeKind::Text(String::from("uno"))
-                            )])
-                        },
-                    ],
-                    TildeCondKind::Colon
-                ))
-            )
-        );
-
-        let mut case = Cursor::new("~[cero~;uno~:;dos~]");
-
-        assert_eq!(
-            Tilde::parse_cond(&mut case)?,
-            Tilde::new(
-                19,
-                TildeKind::Cond((
-                    vec![
-                        Tilde {
-                            len: 4,
-                            value: TildeKind::VecTilde(vec![Tilde::new(
-                                4,
-                                TildeKind::Text(String::from("cero"))
-                            )])
-                        },
-                        Tilde {
-                            len: 3,
-                            value: TildeKind::VecTilde(vec![Tilde::new(
-                                3,
-                                TildeKind::Text(String::from("uno"))
-                            )])
-                        },
-                        Tilde {
-                            len: 3,
-                            value: TildeKind::VecTilde(vec![Tilde::new(
-                                3,
-                                TildeKind::Text(String::from("dos"))
-                            )])
-                        },
-                    ],
-                    TildeCondKind::Nil(true)
-                ))
-            )
-        );
-
-        let mut case = Cursor::new("~#[NONE~;~a~;~a and ~a~:;~a, ~a~]");
-
-        assert_eq!(
-            Tilde::parse_cond(&mut case)?,
-            Tilde::new(
-                33,
-                TildeKind::Cond((
-                    vec![
-                        Tilde {
-                            len: 4,
-                            value: TildeKind::VecTilde(vec![Tilde::new(
-                                4,
-                                TildeKind::Text(String::from("NONE"))
-                            )])
-                        },
-                        Tilde {
-                            len: 2,
-                            value: TildeKind::VecTilde(vec![Tilde::new(2, TildeKind::Va)])
-                        },
-                        Tilde {
-                            len: 9,
-                            value: TildeKind::VecTilde(vec![
-                                Tilde {
-                                    len: 2,
-                                    value: TildeKind::Va
-                                },
-                                Tilde {
-                                    len: 5,
-                                    value: TildeKind::Text(String::from(" and "))
-                                },
-                                Tilde {
-                                    len: 2,
-                                    value: TildeKind::Va,
-                                }
-                            ]),
-                        },
-                        Tilde {
-                            len: 6,
-                            value: TildeKind::VecTilde(vec![
-                                Tilde {
-                                    len: 2,
-                                    value: TildeKind::Va
-                                },
-                                Tilde {
-                                    len: 2,
-                                    value: TildeKind::Text(String::from(", "))
-                                },
-                                Tilde {
-                                    len: 2,
-                                    value: TildeKind::Va,
-                                }
-                            ]),
-                        },
-                    ],
-                    TildeCondKind::Sharp
-                ))
-            )
-        );
-
-        let mut case = Cursor::new("~@[x = ~a ~]~@[y = ~a~]");
-        assert_eq!(
-            Tilde::parse_cond(&mut case)?,
-            Tilde::new(
-                12,
-                TildeKind::Cond((
-                    vec![Tilde {
-                        len: 7,
-                        value: TildeKind::VecTilde(vec![
-                            Tilde {
-                                len: 4,
-                                value: TildeKind::Text("x = ".into())
-                            },
-                            Tilde {
-                                len: 2,
-                                value: TildeKind::Va
-                            },
-                            Tilde {
-                                len: 1,
-                                value: TildeKind::Text(" ".into())
-                            }
-                        ])
-                    }],
-                    TildeCondKind::At
-                ))
-            )
-        );
-
-        // parse the second part
-        assert_eq!(
-            Tilde::parse_cond(&mut case)?,
-            Tilde::new(
-                11,
-                TildeKind::Cond((
-                    vec![Tilde {
-                        len: 6,
-                        value: TildeKind::VecTilde(vec![
-                            Tilde {
-                                len: 4,
-                                value: TildeKind::Text("y = ".into())
-                            },
-                            Tilde {
-                                len: 2,
-                                value: TildeKind::Va
-                            },
-                        ])
-                    }],
-                    TildeCondKind::At
-                ))
-            )
-        );
-
-        Ok(())
-    }
-
-    #[test]
-    fn test_parse_star() -> Result<(), Box<dyn std::error::Error>> {
-        let mut case = Cursor::new("~:*");
-        assert_eq!(
-            Tilde::parse(&mut case)?,
-            Tilde::new(3, TildeKind::Star(StarKind::Hop))
-        );
-
-        let mut case = Cursor::new("~*");
-        assert_eq!(
-            Tilde::parse(&mut case)?,
-            Tilde::new(2, TildeKind::Star(StarKind::Skip))
-        );
-        Ok(())
-    }
-
-    #[test]
-    fn test_parse_tildes() -> Result<(), Box<dyn std::error::Error>> {
-        let mut case = Cursor::new("~9~");
-        assert_eq!(
-            Tilde::parse(&mut case)?,
-            Tilde::new(3, TildeKind::Tildes(9))
-        );
-
-        let mut case = Cursor::new("~0~");
-        assert_eq!(
-            Tilde::parse(&mut case)?,
-            Tilde::new(3, TildeKind::Tildes(0))
-        );
-        Ok(())
-    }
-
-    #[test]
-    fn test_parse_standard() -> Result<(), Box<dyn std::error::Error>> {
-        let mut case = Cursor::new("~s");
-        assert_eq!(Tilde::parse(&mut case)?, Tilde::new(2, TildeKind::Standard));
-
-        let mut case = Cursor::new("~S");
-        assert_eq!(Tilde::parse(&mut case)?, Tilde::new(2, TildeKind::Standard));
-
-        let mut case = Cursor::new("~S superbowl");
-        assert_eq!(Tilde::parse(&mut case)?, Tilde::new(2, TildeKind::Standard));
-
-        Ok(())
-    }
-
-    #[test]
-    fn test_parse_char() -> Result<(), Box<dyn std::error::Error>> {
-        let mut case = Cursor::new("~c");
-        assert_eq!(
-            Tilde::parse(&mut case)?,
-            Tilde::new(2, TildeKind::Char(CharKind::Nil))
-        );
-
-        let mut case = Cursor::new("~@c");
-        assert_eq!(
-            Tilde::parse(&mut case)?,
-            Tilde::new(3, TildeKind::Char(CharKind::At))
-        );
-
-        Ok(())
+/// the column of the end of buf, the count of chars after the last new line
+fn column(buf: &str) -> usize {
+    match buf.rfind('\n') {
+        Some(i) => buf[i + 1..].chars().count(),
+        None => buf.chars().count(),
     }
+}
 
-    #[test]
-    fn test_parse_radix() -> Result<(), Box<dyn std::error::Error>> {
-        //let mut case = Cursor::new("~2,8,'0,' ,R");
-        let mut case = Cursor::new("~2,8,0, ,R");
-        assert_eq!(
-            Tilde::parse(&mut case)?,
-            Tilde::new(
-                10,
-                TildeKind::Radix((Some(2), Some(8), Some('0'), Some(' '), None, None))
-            )
-        );
-
-        let mut case = Cursor::new("~,,,,R");
-        assert_eq!(
-            Tilde::parse(&mut case)?,
-            Tilde::new(6, TildeKind::Radix((None, None, None, None, None, None)))
-        );
-
-        let mut case = Cursor::new("~2,8,0, ,4:R");
-        assert_eq!(
-            Tilde::parse(&mut case)?,
-            Tilde::new(
-                12,
-                TildeKind::Radix((
-                    Some(2),
-                    Some(8),
-                    Some('0'),
-                    Some(' '),
-                    Some(4),
-                    Some(RadixFlag::Colon)
-                ))
-            )
-        );
-
-        let mut case = Cursor::new("~2,8,0, ,10:R");
-        assert_eq!(
-            Tilde::parse(&mut case)?,
-            Tilde::new(
-                13,
-                TildeKind::Radix((
-                    Some(2),
-                    Some(8),
-                    Some('0'),
-                    Some(' '),
-                    Some(10),
-                    Some(RadixFlag::Colon)
-                ))
-            )
-        );
-
-        let mut case = Cursor::new("~3,,, ,2:R");
-        assert_eq!(
-            Tilde::parse(&mut case)?,
-            Tilde::new(
-                10,
-                TildeKind::Radix((
-                    Some(3),
-                    None,
-                    None,
-                    Some(' '),
-                    Some(2),
-                    Some(RadixFlag::Colon)
-                ))
-            )
-        );
-
-        let mut case = Cursor::new("~2:R");
-        assert_eq!(
-            Tilde::parse(&mut case)?,
-            Tilde::new(
-                4,
-                TildeKind::Radix((Some(2), None, None, None, None, Some(RadixFlag::Colon)))
-            )
-        );
-
-        let mut case = Cursor::new("~2R");
-        assert_eq!(
-            Tilde::parse(&mut case)?,
-            Tilde::new(3, TildeKind::Radix((Some(2), None, None, None, None, None)))
-        );
-
-        let mut case = Cursor::new("~R");
-        assert_eq!(
-            Tilde::parse(&mut case)?,
-            Tilde::new(2, TildeKind::Radix((None, None, None, None, None, None)))
-        );
-
-        let mut case = Cursor::new("~:R");
-        assert_eq!(
-            Tilde::parse(&mut case)?,
-            Tilde::new(
-                3,
-                TildeKind::Radix((None, None, None, None, None, Some(RadixFlag::Colon)))
-            )
-        );
-
-        let mut case = Cursor::new("~@R");
-        assert_eq!(
-            Tilde::parse(&mut case)?,
-            Tilde::new(
-                3,
-                TildeKind::Radix((None, None, None, None, None, Some(RadixFlag::At)))
-            )
-        );
-
-        let mut case = Cursor::new("~:@R");
-        assert_eq!(
-            Tilde::parse(&mut case)?,
-            Tilde::new(
-                4,
-                TildeKind::Radix((None, None, None, None, None, Some(RadixFlag::AtColon)))
-            )
-        );
-
-        let mut case = Cursor::new(r#"~2,,,\a,4:R"#);
-        assert_eq!(
-            Tilde::parse(&mut case)?,
-            Tilde::new(
-                11,
-                TildeKind::Radix((
-                    Some(2),
-                    None,
-                    None,
-                    Some('a'),
-                    Some(4),
-                    Some(RadixFlag::Colon)
-                ))
-            )
-        );
+/// the `~colnum,colincT` and `~colrel,colinc@T`
+fn tabulate(buf: &mut String, (n, colinc, relative): &(Option<usize>, Option<usize>, bool)) {
+    let cur = column(buf);
+    let colinc = colinc.unwrap_or(1);
+    let spaces = if *relative {
+        // colrel spaces, then to the column that is a multiple of colinc
+        let colrel = n.unwrap_or(1);
+        if colinc > 0 {
+            (cur + colrel).div_ceil(colinc) * colinc - cur
+        } else {
+            colrel
+        }
+    } else {
+        let colnum = n.unwrap_or(1);
+        if cur < colnum {
+            colnum - cur
+        } else if colinc > 0 {
+            // to the colnum + k * colinc for the smallest positive k
+            colinc - (cur - colnum) % colinc
+        } else {
+            0
+        }
+    };
 
-        Ok(())
-    }
+    buf.extend(std::iter::repeat_n(' ', spaces));
 }
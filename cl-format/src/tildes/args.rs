@@ -10,6 +10,9 @@ pub struct Args<'a, 'arg> {
 
     /// need mutate borrow in immutate Args
     ind: RefCell<usize>,
+
+    /// if these args are the last sublist of `~:{` and `~:@{`, for `~:^`
+    last_sublist: bool,
 }
 
 impl<'a, 'arg> Args<'a, 'arg> {
@@ -18,6 +21,7 @@ impl<'a, 'arg> Args<'a, 'arg> {
             len: i.len(),
             inner: Cow::Owned(i),
             ind: RefCell::new(0),
+            last_sublist: true,
         }
     }
 
@@ -26,6 +30,7 @@ impl<'a, 'arg> Args<'a, 'arg> {
             len: i.len(),
             inner: Cow::Borrowed(i),
             ind: RefCell::new(0),
+            last_sublist: true,
         }
     }
 
@@ -46,6 +51,30 @@ impl<'a, 'arg> Args<'a, 'arg> {
         Some(*r)
     }
 
+    /// the index of the next arg
+    pub fn index(&self) -> usize {
+        *self.ind.borrow()
+    }
+
+    /// set the index of the next arg, None if it is out of the args
+    pub fn set_index(&self, ind: usize) -> Option<()> {
+        if ind > self.len {
+            return None;
+        }
+
+        *self.ind.borrow_mut() = ind;
+        Some(())
+    }
+
+    pub(crate) fn with_last_sublist(mut self, last: bool) -> Self {
+        self.last_sublist = last;
+        self
+    }
+
+    pub(crate) fn is_last_sublist(&self) -> bool {
+        self.last_sublist
+    }
+
     pub fn left_count(&self) -> usize {
         self.len - *self.ind.borrow()
     }
@@ -1,4 +1,5 @@
 use radix_fmt::Radix;
+use std::borrow::Cow;
 use std::fmt::Write;
 use std::iter::{self, successors};
 
@@ -11,29 +12,212 @@ multi_tilde_impl!(
     TildeKindDigit,
     [i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize],
     self,
+    tkind,
     buf,
     {
-        buf.push_str(self.to_string().as_str());
-        Ok(())
+        match tkind {
+            TildeKind::Digit((mincol, padchar, commachar, comma_interval, flag)) => format_helper(
+                buf,
+                self.to_string(),
+                mincol,
+                padchar,
+                commachar,
+                comma_interval,
+                flag,
+            ),
+            _ => Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to Digit",
+            )),
+        }
+    }
+);
+
+//========================================
+// TildeKindBinary, TildeKindOctal, TildeKindHex
+//========================================
+/// format the integer by `~B`, `~O`, or `~X`, `abs` is the absolute value
+fn format_in_radix(
+    buf: &mut String,
+    negative: bool,
+    abs: u128,
+    tkind: &TildeKind,
+) -> Result<(), TildeError> {
+    let sign = if negative { "-" } else { "" };
+    let (s, (mincol, padchar, commachar, comma_interval, flag)) = match tkind {
+        TildeKind::Binary(params) => (format!("{}{:b}", sign, abs), params),
+        TildeKind::Octal(params) => (format!("{}{:o}", sign, abs), params),
+        TildeKind::Hex(params) => (format!("{}{:X}", sign, abs), params),
+        _ => {
+            return Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to Binary, Octal, or Hex",
+            ))
+        }
+    };
+
+    format_helper(buf, s, mincol, padchar, commachar, comma_interval, flag)
+}
+
+multi_tilde_impl!(
+    TildeKindBinary,
+    [i8, i16, i32, i64, i128, isize],
+    self,
+    tkind,
+    buf,
+    {
+        #[allow(clippy::unnecessary_cast)]
+        let abs = self.unsigned_abs() as u128;
+        format_in_radix(buf, *self < 0, abs, tkind)
+    }
+);
+
+multi_tilde_impl!(
+    TildeKindBinary,
+    [u8, u16, u32, u64, u128, usize],
+    self,
+    tkind,
+    buf,
+    {
+        #[allow(clippy::unnecessary_cast)]
+        let abs = *self as u128;
+        format_in_radix(buf, false, abs, tkind)
+    }
+);
+
+multi_tilde_impl!(
+    TildeKindOctal,
+    [i8, i16, i32, i64, i128, isize],
+    self,
+    tkind,
+    buf,
+    {
+        #[allow(clippy::unnecessary_cast)]
+        let abs = self.unsigned_abs() as u128;
+        format_in_radix(buf, *self < 0, abs, tkind)
+    }
+);
+
+multi_tilde_impl!(
+    TildeKindOctal,
+    [u8, u16, u32, u64, u128, usize],
+    self,
+    tkind,
+    buf,
+    {
+        #[allow(clippy::unnecessary_cast)]
+        let abs = *self as u128;
+        format_in_radix(buf, false, abs, tkind)
+    }
+);
+
+multi_tilde_impl!(
+    TildeKindHex,
+    [i8, i16, i32, i64, i128, isize],
+    self,
+    tkind,
+    buf,
+    {
+        #[allow(clippy::unnecessary_cast)]
+        let abs = self.unsigned_abs() as u128;
+        format_in_radix(buf, *self < 0, abs, tkind)
+    }
+);
+
+multi_tilde_impl!(
+    TildeKindHex,
+    [u8, u16, u32, u64, u128, usize],
+    self,
+    tkind,
+    buf,
+    {
+        #[allow(clippy::unnecessary_cast)]
+        let abs = *self as u128;
+        format_in_radix(buf, false, abs, tkind)
     }
 );
 
 //========================================
 // TildeKindChar
 //========================================
+/// the names of the ascii chars cannot be seen
+const CHAR_NAMES: [&str; 33] = [
+    "Nul",
+    "Soh",
+    "Stx",
+    "Etx",
+    "Eot",
+    "Enq",
+    "Ack",
+    "Bel",
+    "Backspace",
+    "Tab",
+    "Newline",
+    "Vt",
+    "Page",
+    "Return",
+    "So",
+    "Si",
+    "Dle",
+    "Dc1",
+    "Dc2",
+    "Dc3",
+    "Dc4",
+    "Nak",
+    "Syn",
+    "Etb",
+    "Can",
+    "Em",
+    "Sub",
+    "Esc",
+    "Fs",
+    "Gs",
+    "Rs",
+    "Us",
+    "Space",
+];
+
+/// write the name of char if it is not printing, otherwise the char itself
+fn char_name(c: char, buf: &mut String) {
+    match c as u32 {
+        n @ 0..=32 => buf.push_str(CHAR_NAMES[n as usize]),
+        127 => buf.push_str("Rubout"),
+        n if c.is_control() || c.is_whitespace() => buf.push_str(&format!("U+{:04X}", n)),
+        _ => buf.push(c),
+    }
+}
+
 /// impl, re-define the format method for over writing the default method
 impl TildeKindChar for char {
     fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
         match tkind {
             TildeKind::Char(CharKind::At) => {
-                buf.push_str(format!("'{}'", self).as_str());
+                buf.push_str("#\\");
+                char_name(*self, buf);
                 Ok(())
             }
             TildeKind::Char(CharKind::Nil) => {
                 buf.push_str(self.to_string().as_str());
                 Ok(())
             }
-            _ => Err(TildeError::new(ErrorKind::RevealError, "cannot format to Char").into()),
+            TildeKind::Char(CharKind::Colon) => {
+                char_name(*self, buf);
+                Ok(())
+            }
+            TildeKind::Char(CharKind::AtColon) => {
+                char_name(*self, buf);
+                // the key typed with control
+                match *self as u32 {
+                    n @ 0..=31 => buf.push_str(&format!(" (Control-{})", (n as u8 + 64) as char)),
+                    127 => buf.push_str(" (Control-?)"),
+                    _ => (),
+                }
+                Ok(())
+            }
+            _ => Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to Char",
+            )),
         }
     }
 }
@@ -77,141 +261,710 @@ impl TildeKindVa for Vec<&dyn TildeAble> {
     }
 }
 
+impl<T: std::fmt::Display + ?Sized> TildeKindVa for Disp<'_, T> {
+    fn format(&self, _: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
+        write!(buf, "{}", self.0)
+            .map_err(|e| TildeError::new(ErrorKind::RevealError, e.to_string()))
+    }
+}
+
 //========================================
 // TildeKindLoop
 //========================================
+/// TildeKindLoop is the trait of `~{`, the `Args` reveal the loop, and the lists are the args of it.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be revealed by the TildeKind::Loop directive",
+    note = "implement `TildeKindLoop` for `{Self}` and return it in `TildeAble::into_tildekind_loop`"
+)]
+pub trait TildeKindLoop: Debug {
+    fn format(&self, _tkind: &TildeKind, _buf: &mut String) -> Result<(), TildeError> {
+        Err(TildeError::new(
+            ErrorKind::EmptyImplenmentError,
+            "haven't implenmented yet",
+        ))
+    }
+
+    /// the args inside the list
+    fn args(&self) -> Option<Args<'_, '_>> {
+        None
+    }
+}
+
+/// the type behind `dyn TildeAble` is only known at runtime
+impl TildeKindLoop for dyn TildeAble + '_ {
+    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
+        self.into_tildekind_loop()
+            .ok_or_else(|| TildeError::new(ErrorKind::RevealError, "cannot reveal to Loop"))?
+            .format(tkind, buf)
+    }
+
+    fn args(&self) -> Option<Args<'_, '_>> {
+        self.into_tildekind_loop()?.args()
+    }
+}
+
+/// the args inside the list arg of `~{` and the sublist of `~:{`
+fn list_arg<'x>(arg: &'x dyn TildeAble) -> Result<Args<'x, 'x>, TildeError> {
+    arg.into_tildekind_loop()
+        .and_then(|l| l.args())
+        .ok_or_else(|| TildeError::new(ErrorKind::RevealError, "cannot reveal to Loop"))
+}
+
 impl<'a, 'arg> TildeKindLoop for Args<'a, 'arg> {
+    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
+        let (vv, kind, max) = match tkind {
+            TildeKind::Loop(l) => l,
+            _ => {
+                return Err(TildeError::new(
+                    ErrorKind::RevealError,
+                    "cannot format Arg to Loop",
+                ))
+            }
+        };
+
+        // the empty body takes the control string from the args
+        let body;
+        let vv = if vv.is_empty() {
+            let a = self
+                .pop()
+                .ok_or::<TildeError>(TildeError::new(ErrorKind::FormatError, "run out args"))?;
+            body = control_str_arg(a)?;
+            &body
+        } else {
+            vv.as_ref()
+        };
+
+        match kind {
+            TildeLoopKind::At => reveal_steps(vv, self, *max, buf),
+            TildeLoopKind::AtColon => reveal_sublists(vv, self, *max, buf),
+            _ => {
+                let a = self
+                    .pop()
+                    .ok_or::<TildeError>(TildeError::new(ErrorKind::FormatError, "run out args"))?;
+                let list = list_arg(a)?;
+                match kind {
+                    TildeLoopKind::Colon => reveal_sublists(vv, &list, *max, buf),
+                    // ~{~:} runs at least once
+                    TildeLoopKind::Nil if list.left_count() == 0 => Ok(()),
+                    _ => reveal_steps(vv, &list, *max, buf),
+                }
+            }
+        }
+    }
+}
+
+/// every iteration step of `~{` and `~@{` takes the args it needs, until no args left
+fn reveal_steps(
+    vv: &[Tilde],
+    args: &Args,
+    max: Option<usize>,
+    buf: &mut String,
+) -> Result<(), TildeError> {
+    let mut n = 0;
+    while Some(n) != max {
+        n += 1;
+
+        for t in vv {
+            if let Reveal::Escape(_) = t.reveal(args, buf)? {
+                return Ok(());
+            }
+        }
+        //dbg!(args);
+        if args.left_count() == 0 {
+            break;
+        }
+    }
+
+    Ok(())
+}
+
+/// every arg is the sublist of one iteration step of `~:{` and `~:@{`
+fn reveal_sublists(
+    vv: &[Tilde],
+    args: &Args,
+    max: Option<usize>,
+    buf: &mut String,
+) -> Result<(), TildeError> {
+    let mut n = 0;
+    // check the max before taking the arg, the rest args are left for the next tildes
+    while Some(n) != max {
+        let Some(a) = args.pop() else { break };
+        n += 1;
+
+        let last = args.left_count() == 0 || Some(n) == max;
+        if let Reveal::Escape(_) = reveal_sublist(vv, list_arg(a)?, last, buf)? {
+            break;
+        }
+    }
+    Ok(())
+}
+
+/// one iteration step of `~:{` and `~:@{`, `last` if it is the last sublist for `~:^`
+fn reveal_sublist(
+    vv: &[Tilde],
+    sublist: Args,
+    last: bool,
+    buf: &mut String,
+) -> Result<Reveal, TildeError> {
+    let args = sublist.with_last_sublist(last);
+    for t in vv {
+        match t.reveal(&args, buf)? {
+            // ~^ only ends this step, ~:^ goes up to end the whole iteration
+            Reveal::Escape(false) => break,
+            r @ Reveal::Escape(true) => return Ok(r),
+            Reveal::Continue => (),
+        }
+    }
+    Ok(Reveal::Continue)
+}
+
+impl TildeKindLoop for Vec<&dyn TildeAble> {
+    fn args(&self) -> Option<Args<'_, '_>> {
+        Some(Args::from(self.as_slice()))
+    }
+}
+
+//========================================
+// TildeKindLoopEnd
+//========================================
+/// escape upward by the `Reveal::Escape` if the condition is true
+///
+/// without parameters, the condition is no args left, or being the last sublist for `~:^`.
+/// The parameters are positional, `~,3^` compares the omitted n with 3.
+fn loop_end(tkind: &TildeKind, left: usize, last_sublist: bool) -> Result<Reveal, TildeError> {
+    let (n, m, p, colon) = match tkind {
+        TildeKind::LoopEnd(params) => params,
+        _ => {
+            return Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to LoopEnd",
+            ))
+        }
+    };
+
+    let end = match (n, m, p) {
+        (_, _, Some(p)) => matches!((n, m), (Some(n), Some(m)) if n <= m && m <= p),
+        (_, Some(m), None) => *n == Some(*m),
+        (Some(n), None, None) => *n == 0,
+        (None, None, None) if *colon => last_sublist,
+        (None, None, None) => left == 0,
+    };
+
+    if end {
+        Ok(Reveal::Escape(*colon))
+    } else {
+        Ok(Reveal::Continue)
+    }
+}
+
+impl<'a, 'arg> TildeKindLoopEnd for Args<'a, 'arg> {
+    fn format(&self, tkind: &TildeKind, _buf: &mut String) -> Result<Reveal, TildeError> {
+        loop_end(tkind, self.left_count(), self.is_last_sublist())
+    }
+}
+
+/// there is no args
+impl TildeKindLoopEnd for TildeNil {
+    fn format(&self, tkind: &TildeKind, _buf: &mut String) -> Result<Reveal, TildeError> {
+        loop_end(tkind, 0, true)
+    }
+}
+
+//========================================
+// TildeKindParams
+//========================================
+/// TildeKindParams is the trait of the tilde with `V` or `#` parameters, the `Args` reveal it, and
+/// the args taken by `V` are the values of the parameters.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be revealed by the tilde with `V` or `#` parameters",
+    note = "implement `TildeKindParams` for `{Self}` and return it in `TildeAble::into_tildekind_params`"
+)]
+pub trait TildeKindParams: Debug {
+    fn format(&self, _tilde: &Tilde, _buf: &mut String) -> Result<Reveal, TildeError> {
+        Err(TildeError::new(
+            ErrorKind::EmptyImplenmentError,
+            "haven't implenmented yet",
+        ))
+    }
+
+    /// the value of the parameter, None if it is nil, which is the omitted parameter
+    fn param(&self) -> Result<Option<ParamValue>, TildeError> {
+        Err(TildeError::new(
+            ErrorKind::EmptyImplenmentError,
+            "haven't implenmented yet",
+        ))
+    }
+}
+
+/// the type behind `dyn TildeAble` is only known at runtime
+impl TildeKindParams for dyn TildeAble + '_ {
+    fn format(&self, tilde: &Tilde, buf: &mut String) -> Result<Reveal, TildeError> {
+        params_view(self)?.format(tilde, buf)
+    }
+
+    fn param(&self) -> Result<Option<ParamValue>, TildeError> {
+        params_view(self)?.param()
+    }
+}
+
+fn params_view(arg: &dyn TildeAble) -> Result<&dyn TildeKindParams, TildeError> {
+    arg.into_tildekind_params()
+        .ok_or_else(|| TildeError::new(ErrorKind::RevealError, "cannot reveal to Params"))
+}
+
+impl<'a, 'arg> TildeKindParams for Args<'a, 'arg> {
+    fn format(&self, tilde: &Tilde, buf: &mut String) -> Result<Reveal, TildeError> {
+        let mut kind = tilde.value().clone();
+        for (n, p) in tilde.params() {
+            let value = match p {
+                TildeParam::Arg => {
+                    let a = self.pop().ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::FormatError,
+                        "run out args",
+                    ))?;
+                    params_view(a)?.param()?
+                }
+                TildeParam::ArgsCount => Some(ParamValue::Int(self.left_count() as i128)),
+            };
+            kind.set_param(*n, value)?;
+        }
+
+        // like ~v^, the tilde may escape upward
+        kind.reveal(self, buf)
+    }
+}
+
+macro_rules! params_impl {
+    ($($t:ident),+) => {
+        $(
+            impl TildeKindParams for $t {
+                #[allow(clippy::unnecessary_fallible_conversions)]
+                fn param(&self) -> Result<Option<ParamValue>, TildeError> {
+                    let n = i128::try_from(*self).map_err(|_| {
+                        TildeError::new(ErrorKind::OutOfRange, format!("parameter {} is out of range", self))
+                    })?;
+                    Ok(Some(ParamValue::Int(n)))
+                }
+            }
+        )+
+    };
+}
+
+params_impl!(i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize);
+
+impl TildeKindParams for char {
+    fn param(&self) -> Result<Option<ParamValue>, TildeError> {
+        Ok(Some(ParamValue::Char(*self)))
+    }
+}
+
+/// None is the nil, as the parameter is omitted
+impl TildeKindParams for Option<&dyn TildeAble> {
+    fn param(&self) -> Result<Option<ParamValue>, TildeError> {
+        match self {
+            Some(a) => params_view(*a)?.param(),
+            None => Ok(None),
+        }
+    }
+}
+
+//========================================
+// TildeKindRecursive
+//========================================
+/// TildeKindRecursive is the trait of `~?`, the `Args` reveal it, and the control strings are the
+/// args of it.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be revealed by the TildeKind::Recursive directive",
+    note = "implement `TildeKindRecursive` for `{Self}` and return it in `TildeAble::into_tildekind_recursive`"
+)]
+pub trait TildeKindRecursive: Debug {
+    fn format(&self, _tkind: &TildeKind, _buf: &mut String) -> Result<(), TildeError> {
+        Err(TildeError::new(
+            ErrorKind::EmptyImplenmentError,
+            "haven't implenmented yet",
+        ))
+    }
+
+    /// the parsed control string, only the strings are parsed here
+    fn control_str(&self) -> Result<Cow<'_, ControlStr<'_>>, TildeError> {
+        Err(TildeError::new(
+            ErrorKind::EmptyImplenmentError,
+            "haven't implenmented yet",
+        ))
+    }
+}
+
+/// the type behind `dyn TildeAble` is only known at runtime
+impl TildeKindRecursive for dyn TildeAble + '_ {
+    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
+        control_str_view(self)?.format(tkind, buf)
+    }
+
+    fn control_str(&self) -> Result<Cow<'_, ControlStr<'_>>, TildeError> {
+        control_str_view(self)?.control_str()
+    }
+}
+
+fn control_str_view(arg: &dyn TildeAble) -> Result<&dyn TildeKindRecursive, TildeError> {
+    arg.into_tildekind_recursive()
+        .ok_or_else(|| TildeError::new(ErrorKind::RevealError, "cannot reveal to Recursive"))
+}
+
+impl<'a, 'arg> TildeKindRecursive for Args<'a, 'arg> {
+    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
+        match tkind {
+            TildeKind::Recursive(at) => {
+                let a = self
+                    .pop()
+                    .ok_or::<TildeError>(TildeError::new(ErrorKind::FormatError, "run out args"))?;
+                let cs = control_str_view(a)?.control_str()?;
+
+                // ~^ ends the recursive control string only
+                if *at {
+                    cs.reveal_to(self, buf)?;
+                } else {
+                    let l = self.pop().ok_or::<TildeError>(TildeError::new(
+                        ErrorKind::FormatError,
+                        "run out args",
+                    ))?;
+                    cs.reveal_to(&list_arg(l)?, buf)?;
+                }
+
+                Ok(())
+            }
+            _ => Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format Arg to Recursive",
+            )),
+        }
+    }
+}
+
+/// the tildes of the control string arg of the empty body of `~{~}`
+fn control_str_arg(arg: &dyn TildeAble) -> Result<Vec<Tilde>, TildeError> {
+    Ok(control_str_view(arg)?.control_str()?.to_tildes())
+}
+
+fn parse_control_str(s: &str) -> Result<Cow<'_, ControlStr<'_>>, TildeError> {
+    let tildes = Tilde::scan(s).map_err(|(_, e)| e)?;
+    Ok(Cow::Owned(ControlStr::from_parsed(s, tildes)))
+}
+
+impl TildeKindRecursive for String {
+    fn control_str(&self) -> Result<Cow<'_, ControlStr<'_>>, TildeError> {
+        parse_control_str(self)
+    }
+}
+
+impl TildeKindRecursive for &str {
+    fn control_str(&self) -> Result<Cow<'_, ControlStr<'_>>, TildeError> {
+        parse_control_str(self)
+    }
+}
+
+/// the tildes parsed already are used again
+impl<'a> TildeKindRecursive for ControlStr<'a> {
+    fn control_str(&self) -> Result<Cow<'_, ControlStr<'_>>, TildeError> {
+        Ok(Cow::Borrowed(self))
+    }
+}
+
+//========================================
+// TildeKindPlural
+//========================================
+impl<'a, 'arg> TildeKindPlural for Args<'a, 'arg> {
     fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
         match tkind {
-            // self[0] is the Vec<&dyn TildeAble> of loop
-            TildeKind::Loop((_, TildeLoopKind::Nil | TildeLoopKind::NilColon)) => {
+            TildeKind::Plural((colon, _)) => {
+                // use the last arg again
+                if *colon && self.back().is_none() {
+                    return Err(TildeError::new(
+                        ErrorKind::FormatError,
+                        "no previous arg for ~:P",
+                    ));
+                }
                 let a = self
                     .pop()
                     .ok_or::<TildeError>(TildeError::new(ErrorKind::FormatError, "run out args"))?;
-                tkind.match_reveal(a, buf)
-            }
-            TildeKind::Loop((vv, TildeLoopKind::At)) => {
-                'a: loop {
-                    for t in vv {
-                        if let TildeKind::LoopEnd = t.value {
-                            if self.left_count() != 0 {
-                                continue;
-                            } else {
-                                break 'a;
-                            }
-                        }
-                        t.reveal(self, buf)?;
+                tkind.reveal(a, buf)?;
+                Ok(())
+            }
+            _ => Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format Arg to Plural",
+            )),
+        }
+    }
+}
+
+multi_tilde_impl!(
+    TildeKindPlural,
+    [i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize],
+    self,
+    tkind,
+    buf,
+    {
+        match tkind {
+            TildeKind::Plural((_, at)) => {
+                buf.push_str(match (*self == 1, at) {
+                    (true, false) => "",
+                    (false, false) => "s",
+                    (true, true) => "y",
+                    (false, true) => "ies",
+                });
+                Ok(())
+            }
+            _ => Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to Plural",
+            )),
+        }
+    }
+);
+
+//========================================
+// TildeKindJustify
+//========================================
+/// reveal the segments, then distribute the padding between them
+fn reveal_justify(
+    arg: &dyn TildeAble,
+    tkind: &TildeKind,
+    buf: &mut String,
+) -> Result<(), TildeError> {
+    let (segments, (mincol, colinc, minpad, padchar), colon, at, overflow) = match tkind {
+        TildeKind::Justify(j) => j,
+        _ => {
+            return Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to Justify",
+            ))
+        }
+    };
+
+    let start = buf.len();
+    let mut texts = vec![];
+    for seg in segments.iter() {
+        // reveal in buf for the column of ~& and ~T, then take the segment out
+        let reveal = seg.reveal(arg, buf)?;
+        let s = buf.split_off(start);
+        // only the segments completely processed are justified
+        if let Reveal::Escape(_) = reveal {
+            break;
+        }
+        texts.push(s);
+    }
+    let prefix = match overflow {
+        Some(_) if !texts.is_empty() => Some(texts.remove(0)),
+        _ => None,
+    };
+
+    // one segment without modifiers is right justified
+    let pad_left = *colon || (!*at && texts.len() == 1);
+    let mut gaps = texts.len().saturating_sub(1) + pad_left as usize + *at as usize;
+    if gaps == 0 {
+        // no segment at all
+        gaps = 1;
+    }
+
+    let chars = texts.iter().map(|s| s.chars().count()).sum::<usize>() + gaps * minpad;
+    let width = if chars > *mincol {
+        mincol + (chars - mincol).div_ceil((*colinc).max(1)) * (*colinc).max(1)
+    } else {
+        *mincol
+    };
+    let mut padding = width - chars + gaps * minpad;
+
+    if let (Some(prefix), Some((spare, line_width))) = (prefix, overflow) {
+        if column(buf) + width + spare > *line_width {
+            buf.push_str(&prefix);
+        }
+    }
+
+    let mut do_padding = |buf: &mut String| {
+        // the earlier gaps get the extra padding
+        let n = padding.div_ceil(gaps);
+        padding -= n;
+        gaps -= 1;
+        buf.extend(iter::repeat_n(*padchar, n));
+    };
+
+    if pad_left {
+        do_padding(buf);
+    }
+    for (i, s) in texts.iter().enumerate() {
+        if i > 0 {
+            do_padding(buf);
+        }
+        buf.push_str(s);
+    }
+    if *at {
+        do_padding(buf);
+    }
+
+    Ok(())
+}
+
+impl TildeKindJustify for TildeNil {
+    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
+        reveal_justify(self, tkind, buf)
+    }
+}
+
+impl<'a, 'arg> TildeKindJustify for Args<'a, 'arg> {
+    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
+        reveal_justify(self, tkind, buf)
+    }
+}
+
+//========================================
+// TildeKindCaseConv
+//========================================
+/// convert the case of the output inside `~(...~)`
+fn convert_case(kind: &CaseConvKind, s: &str) -> String {
+    match kind {
+        CaseConvKind::Lower => s.to_lowercase(),
+        CaseConvKind::Upper => s.to_uppercase(),
+        CaseConvKind::Capitalize | CaseConvKind::CapitalizeFirst => {
+            // a word is the alphanumeric chars
+            let mut result = String::with_capacity(s.len());
+            let mut in_word = false;
+            let mut first_word_done = false;
+            for c in s.chars() {
+                if c.is_alphanumeric() {
+                    if !in_word && !first_word_done {
+                        result.extend(c.to_uppercase());
+                    } else {
+                        result.extend(c.to_lowercase());
                     }
-                    //dbg!(self);
-                    if self.left_count() == 0 {
-                        break;
+                    in_word = true;
+                } else {
+                    if in_word && *kind == CaseConvKind::CapitalizeFirst {
+                        first_word_done = true;
                     }
+                    in_word = false;
+                    result.push(c);
                 }
+            }
+            result
+        }
+    }
+}
 
-                Ok(())
+/// reveal the tildes inside the case conversion to buf, then convert them
+fn reveal_case_conv(
+    arg: &dyn TildeAble,
+    tkind: &TildeKind,
+    buf: &mut String,
+) -> Result<Reveal, TildeError> {
+    match tkind {
+        TildeKind::CaseConv((vv, kind)) => {
+            let start = buf.len();
+            let mut result = Ok(Reveal::Continue);
+            for t in vv.iter() {
+                result = t.reveal(arg, buf);
+                if !matches!(result, Ok(Reveal::Continue)) {
+                    break;
+                }
             }
-            _ => Err(TildeError::new(ErrorKind::RevealError, "cannot format Arg to Loop").into()),
+
+            // the output before ~^ is converted as well
+            let converted = convert_case(kind, &buf[start..]);
+            buf.truncate(start);
+            buf.push_str(&converted);
+            result
+        }
+        _ => Err(TildeError::new(
+            ErrorKind::RevealError,
+            "cannot format to CaseConv",
+        )),
+    }
+}
+
+impl TildeKindCaseConv for TildeNil {
+    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<Reveal, TildeError> {
+        reveal_case_conv(self, tkind, buf)
+    }
+}
+
+impl<'a, 'arg> TildeKindCaseConv for Args<'a, 'arg> {
+    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<Reveal, TildeError> {
+        reveal_case_conv(self, tkind, buf)
+    }
+}
+
+//========================================
+// TildeKindCond
+//========================================
+/// TildeKindCond is the trait of the arg picking the clause of `~[`, the clause is revealed
+/// with the rest args when the `Args` reveal it.
+#[diagnostic::on_unimplemented(
+    message = "`{Self}` cannot be revealed by the TildeKind::Cond directive",
+    note = "implement `TildeKindCond` for `{Self}` and return it in `TildeAble::into_tildekind_cond`"
+)]
+pub trait TildeKindCond: Debug {
+    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<Reveal, TildeError> {
+        match self.clause(tkind)? {
+            Some(tt) => tt.reveal(&TildeNil, buf),
+            None => Ok(Reveal::Continue),
         }
     }
+
+    /// the clause of `~[` and `~:[` picked by the arg, None if no clause is picked
+    fn clause<'t>(&self, _tkind: &'t TildeKind) -> Result<Option<&'t Tilde>, TildeError> {
+        Err(TildeError::new(
+            ErrorKind::EmptyImplenmentError,
+            "haven't implenmented yet",
+        ))
+    }
 }
 
-impl<'a> TildeKindLoop for Vec<&dyn TildeAble> {
-    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
-        match tkind {
-            TildeKind::Loop((_, TildeLoopKind::Nil)) => {
-                let mut new_kind = tkind.clone();
-
-                match &mut new_kind {
-                    TildeKind::Loop((_, k @ TildeLoopKind::Nil)) => {
-                        if self.len() != 0 {
-                            *k = TildeLoopKind::At
-                        } else {
-                            return Ok(());
-                        }
-                    }
-                    _ => unreachable!(),
-                };
-                new_kind.match_reveal(&Args::from(self.as_slice()), buf)
-            }
-            TildeKind::Loop((_, TildeLoopKind::NilColon)) => {
-                let mut new_kind = tkind.clone();
-                match &mut new_kind {
-                    TildeKind::Loop((_, k @ TildeLoopKind::NilColon)) => *k = TildeLoopKind::At,
-                    _ => unreachable!(),
-                };
-                new_kind.match_reveal(&Args::from(self.as_slice()), buf)
-            }
-            _ => Err(TildeError::new(ErrorKind::RevealError, "cannot format Vec to Loop").into()),
-        }
+/// the type behind `dyn TildeAble` is only known at runtime
+impl TildeKindCond for dyn TildeAble + '_ {
+    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<Reveal, TildeError> {
+        cond_view(self)?.format(tkind, buf)
+    }
+
+    fn clause<'t>(&self, tkind: &'t TildeKind) -> Result<Option<&'t Tilde>, TildeError> {
+        cond_view(self)?.clause(tkind)
     }
 }
 
-//========================================
-// TildeKindCond
-//========================================
+fn cond_view(arg: &dyn TildeAble) -> Result<&dyn TildeKindCond, TildeError> {
+    arg.into_tildekind_cond()
+        .ok_or_else(|| TildeError::new(ErrorKind::RevealError, "cannot reveal to Cond"))
+}
+
 impl TildeKindCond for usize {
-    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
-        //dbg!(self);
+    fn clause<'t>(&self, tkind: &'t TildeKind) -> Result<Option<&'t Tilde>, TildeError> {
         match tkind {
-            TildeKind::Cond((vv, TildeCondKind::Nil(true))) => match vv.get(*self) {
-                Some(tt) => {
-                    tt.reveal(&TildeNil, buf)?;
-                    Ok(())
-                }
-                None => {
-                    let last = vv.len() - 1;
-                    match vv.get(last) {
-                        Some(tt) => {
-                            tt.reveal(&TildeNil, buf)?;
-                            Ok(())
-                        }
-                        None => Ok(()),
-                    }
-                }
-            },
-            TildeKind::Cond((vv, TildeCondKind::Nil(false))) => match vv.get(*self) {
-                Some(tt) => {
-                    tt.reveal(&TildeNil, buf)?;
-                    Ok(())
-                }
-                None => Ok(()),
-            },
-            _ => Err(TildeError::new(ErrorKind::RevealError, "cannot format to Cond").into()),
+            // the default clause ~:; takes the numbers out of range
+            TildeKind::Cond((vv, TildeCondKind::Nil(true))) => Ok(vv.get(*self).or(vv.last())),
+            TildeKind::Cond((vv, TildeCondKind::Nil(false))) => Ok(vv.get(*self)),
+            _ => Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to Cond",
+            )),
         }
     }
 }
 
 impl TildeKindCond for bool {
-    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
+    fn clause<'t>(&self, tkind: &'t TildeKind) -> Result<Option<&'t Tilde>, TildeError> {
         match tkind {
-            TildeKind::Cond((vv, TildeCondKind::Colon)) => {
-                if *self {
-                    vv.get(1)
-                        .ok_or::<TildeError>(TildeError::new(
-                            ErrorKind::FormatError,
-                            "cannot get tilde",
-                        ))?
-                        .reveal(&TildeNil, buf)?;
-
-                    Ok(())
-                } else {
-                    vv.get(0)
-                        .ok_or::<TildeError>(TildeError::new(
-                            ErrorKind::FormatError,
-                            "cannot get tilde",
-                        ))?
-                        .reveal(&TildeNil, buf)?;
-
-                    Ok(())
-                }
-            }
-            _ => Err(TildeError::new(ErrorKind::RevealError, "cannot format to Cond").into()),
+            TildeKind::Cond((vv, TildeCondKind::Colon)) => vv
+                .get(*self as usize)
+                .map(Some)
+                .ok_or(TildeError::new(ErrorKind::FormatError, "cannot get tilde")),
+            _ => Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to Cond",
+            )),
         }
     }
 }
 
 impl TildeKindCond for Option<&dyn TildeAble> {
-    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
+    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<Reveal, TildeError> {
         match tkind {
             TildeKind::Cond((vv, TildeCondKind::At)) => match self {
                 Some(a) => {
@@ -220,35 +973,49 @@ impl TildeKindCond for Option<&dyn TildeAble> {
                     // VecTilde need the vec
                     // TildeCondKind::At only accept one arg
 
-                    k.match_reveal(&Args::from([*a]), buf)
+                    k.reveal(&Args::from([*a]), buf)
                 }
-                None => Ok(()),
+                None => Ok(Reveal::Continue),
             },
-            _ => Err(TildeError::new(ErrorKind::RevealError, "cannot format to Cond").into()),
+            _ => Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to Cond",
+            )),
         }
     }
 }
 
 impl<'a, 'arg> TildeKindCond for Args<'a, 'arg> {
-    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
+    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<Reveal, TildeError> {
         match tkind {
             TildeKind::Cond((vv, TildeCondKind::Sharp)) => {
                 let l = self.left_count();
                 if l >= vv.len() {
-                    vv[vv.len() - 1].reveal(self, buf)?;
-                    Ok(())
+                    vv[vv.len() - 1].reveal(self, buf)
                 } else {
-                    vv[l].reveal(self, buf)?;
-                    Ok(())
+                    vv[l].reveal(self, buf)
                 }
             }
+            TildeKind::Cond((_, TildeCondKind::At)) => {
+                let a = self
+                    .pop()
+                    .ok_or::<TildeError>(TildeError::new(ErrorKind::FormatError, "run out args"))?;
+                tkind.reveal(a, buf)
+            }
             TildeKind::Cond((_, _)) => {
                 let a = self
                     .pop()
                     .ok_or::<TildeError>(TildeError::new(ErrorKind::FormatError, "run out args"))?;
-                tkind.match_reveal(a, buf)
+                // the clause takes the rest args, and ~^ inside it sees them
+                match a.clause(tkind)? {
+                    Some(tt) => tt.reveal(self, buf),
+                    None => Ok(Reveal::Continue),
+                }
             }
-            _ => Err(TildeError::new(ErrorKind::RevealError, "cannot format to Cond").into()),
+            _ => Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to Cond",
+            )),
         }
     }
 }
@@ -256,31 +1023,46 @@ impl<'a, 'arg> TildeKindCond for Args<'a, 'arg> {
 //========================================
 // TildeKindVecTilde
 //========================================
+/// reveal the tildes in order until one escapes upward
+fn reveal_tildes(
+    vv: &[Tilde],
+    arg: &dyn TildeAble,
+    buf: &mut String,
+) -> Result<Reveal, TildeError> {
+    for v in vv {
+        if let r @ Reveal::Escape(_) = v.reveal(arg, buf)? {
+            return Ok(r);
+        }
+    }
+    Ok(Reveal::Continue)
+}
+
 impl TildeKindVecTilde for TildeNil {
-    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
+    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<Reveal, TildeError> {
         match tkind {
-            TildeKind::VecTilde(vv) => {
-                for v in vv {
-                    v.reveal(self, buf)?;
-                }
-
-                Ok(())
-            }
-            _ => Err(TildeError::new(ErrorKind::RevealError, "cannot format to VecTilde").into()),
+            TildeKind::VecTilde(vv) => reveal_tildes(vv, self, buf),
+            _ => Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to VecTilde",
+            )),
         }
     }
 }
 
+impl TildeKindVecTilde for Vec<&dyn TildeAble> {
+    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<Reveal, TildeError> {
+        tkind.reveal(&Args::from(self.as_slice()), buf)
+    }
+}
+
 impl<'a, 'arg> TildeKindVecTilde for Args<'a, 'arg> {
-    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
+    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<Reveal, TildeError> {
         match tkind {
-            TildeKind::VecTilde(vv) => {
-                for v in vv {
-                    v.reveal(self, buf)?;
-                }
-                Ok(())
-            }
-            _ => Err(TildeError::new(ErrorKind::RevealError, "cannot format to VecTilde").into()),
+            TildeKind::VecTilde(vv) => reveal_tildes(vv, self, buf),
+            _ => Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to VecTilde",
+            )),
         }
     }
 }
@@ -290,18 +1072,20 @@ impl<'a, 'arg> TildeKindVecTilde for Args<'a, 'arg> {
 //========================================
 impl<'a, 'arg> TildeKindStar for Args<'a, 'arg> {
     fn format(&self, tkind: &TildeKind, _buf: &mut String) -> Result<(), TildeError> {
-        match tkind {
-            TildeKind::Star(StarKind::Hop) => {
-                self.back(); // back to last one, make it hop
-
-                Ok(())
-            }
-            TildeKind::Star(StarKind::Skip) => {
-                self.pop();
-                Ok(())
+        let ind = match tkind {
+            TildeKind::Star(StarKind::Hop(n)) => self.index().checked_sub(*n),
+            TildeKind::Star(StarKind::Skip(n)) => Some(self.index() + n),
+            TildeKind::Star(StarKind::Goto(n)) => Some(*n),
+            _ => {
+                return Err(TildeError::new(
+                    ErrorKind::RevealError,
+                    "cannot format to Star",
+                ))
             }
-            _ => Err(TildeError::new(ErrorKind::RevealError, "cannot format to Star").into()),
-        }
+        };
+
+        ind.and_then(|i| self.set_index(i))
+            .ok_or(TildeError::new(ErrorKind::FormatError, "jump out of args"))
     }
 }
 
@@ -315,6 +1099,13 @@ impl TildeKindStandard for String {
     }
 }
 
+impl<T: Debug + ?Sized> TildeKindStandard for Dbg<'_, T> {
+    fn format(&self, _: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
+        write!(buf, "{:?}", self.0)
+            .map_err(|e| TildeError::new(ErrorKind::RevealError, e.to_string()))
+    }
+}
+
 impl TildeKindStandard for char {
     fn format(&self, _: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
         buf.push_str(format!("'{}'", self).as_str());
@@ -336,11 +1127,7 @@ multi_tilde_impl!(
 //========================================
 // TildeKindRadix
 //========================================
-const NUMERALS: [(usize, [&'static str; 10]); 4] = [
-    (
-        1000,
-        ["", "M", "MM", "MMM", "--", "-", "--", "---", "----", "--"],
-    ),
+const NUMERALS: [(u128, [&'static str; 10]); 3] = [
     (
         100,
         ["", "C", "CC", "CCC", "CD", "D", "DC", "DCC", "DCCC", "CM"],
@@ -355,18 +1142,41 @@ const NUMERALS: [(usize, [&'static str; 10]); 4] = [
     ),
 ];
 
-/// make roman
-fn into_roman(n: usize) -> Result<String, TildeError> {
-    if n > 3999 {
+/// (base, one, five) of the old roman numerals
+const OLD_NUMERALS: [(u128, &str, &str); 3] = [(100, "C", "D"), (10, "X", "L"), (1, "I", "V")];
+
+/// make roman, the old roman numerals are additive, like IIII instead of IV
+fn into_roman(n: u128, old: bool) -> Result<String, TildeError> {
+    let max = if old { 4999 } else { 3999 };
+    if n == 0 || n > max {
         return Err(TildeError::new(
-            ErrorKind::FormatError,
-            "number is too big to reveal as roman numerals",
+            ErrorKind::OutOfRange,
+            format!(
+                "{} cannot be revealed as {}roman numerals, only 1 to {}",
+                n,
+                if old { "old " } else { "" },
+                max
+            ),
         ));
     }
-    Ok(NUMERALS
-        .iter()
-        .map(|&(base, nums)| nums[(n / base) % 10])
-        .collect())
+
+    let mut result = "M".repeat((n / 1000) as usize);
+    if old {
+        for (base, one, five) in OLD_NUMERALS {
+            let d = (n / base) % 10;
+            if d >= 5 {
+                result.push_str(five);
+            }
+            result.push_str(&one.repeat((d % 5) as usize));
+        }
+    } else {
+        result.extend(
+            NUMERALS
+                .iter()
+                .map(|&(base, nums)| nums[((n / base) % 10) as usize]),
+        );
+    }
+    Ok(result)
 }
 
 /// make number
@@ -433,27 +1243,39 @@ const ORDINAL_TENS: [&str; 10] = [
     "ninetieth",
 ];
 
-const ORDERS: [&str; 7] = [
+const ORDERS: [&str; 13] = [
     "zero",
     "thousand",
     "million",
     "billion",
     "trillion",
     "quadrillion",
-    "quintillion", // enough for u64::MAX
+    "quintillion",
+    "sextillion",
+    "septillion",
+    "octillion",
+    "nonillion",
+    "decillion",
+    "undecillion", // enough for u128::MAX
 ];
 
-const ORDINAL_ORDERS: [&str; 7] = [
+const ORDINAL_ORDERS: [&str; 13] = [
     "zeroth",
     "thousandth",
     "millionth",
     "billionth",
     "trillionth",
     "quadrillionth",
-    "quintillionth", // enough for u64::MAX
+    "quintillionth",
+    "sextillionth",
+    "septillionth",
+    "octillionth",
+    "nonillionth",
+    "decillionth",
+    "undecillionth", // enough for u128::MAX
 ];
 
-pub fn into_english(num: usize, buf: &mut String) {
+pub fn into_english(num: u128, buf: &mut String) {
     match num {
         0..=19 => {
             buf.push_str(ONES[num as usize]);
@@ -471,7 +1293,7 @@ pub fn into_english(num: usize, buf: &mut String) {
         }
         100..=999 => format_num(num, 100, "hundred", buf),
         _ => {
-            let (div, order) = successors(Some(1_usize), |v| v.checked_mul(1000_usize))
+            let (div, order) = successors(Some(1_u128), |v| v.checked_mul(1000))
                 .zip(ORDERS.iter())
                 .find(|&(e, _)| e > num / 1000)
                 .unwrap();
@@ -481,7 +1303,7 @@ pub fn into_english(num: usize, buf: &mut String) {
     }
 }
 
-fn format_num(num: usize, div: usize, order: &str, buf: &mut String) {
+fn format_num(num: u128, div: u128, order: &str, buf: &mut String) {
     match (num / div, num % div) {
         (upper, 0) => {
             into_english(upper, buf);
@@ -498,7 +1320,7 @@ fn format_num(num: usize, div: usize, order: &str, buf: &mut String) {
     }
 }
 
-pub fn into_ordinal_english(num: usize, buf: &mut String) {
+pub fn into_ordinal_english(num: u128, buf: &mut String) {
     match num {
         0..=19 => {
             buf.push_str(ORDINAL_ONES[num as usize]);
@@ -516,7 +1338,7 @@ pub fn into_ordinal_english(num: usize, buf: &mut String) {
         }
         100..=999 => format_ordinal_num(num, 100, "hundred", buf),
         _ => {
-            let (div, order) = successors(Some(1_usize), |v| v.checked_mul(1000_usize))
+            let (div, order) = successors(Some(1_u128), |v| v.checked_mul(1000))
                 .zip(ORDERS.iter())
                 .find(|&(e, _)| e > num / 1000)
                 .unwrap();
@@ -531,7 +1353,7 @@ fn orders_to_ordinal(order: &str) -> &str {
     ORDINAL_ORDERS[ORDERS.iter().position(|s| *s == order).unwrap()]
 }
 
-fn format_ordinal_num(num: usize, div: usize, order: &str, buf: &mut String) {
+fn format_ordinal_num(num: u128, div: u128, order: &str, buf: &mut String) {
     //dbg!(&num);
     //dbg!(&div);
     match (num / div, num % div) {
@@ -550,6 +1372,10 @@ fn format_ordinal_num(num: usize, div: usize, order: &str, buf: &mut String) {
     }
 }
 
+/// format the integer string `orginal` with the sign, commas, and padding.
+///
+/// The sign is inside the padding, the `@` prints the `+` for the non-negative number.
+/// The `:` groups the digits by the comma_interval (default 3) with the commachar (default ',').
 fn format_helper(
     buf: &mut String,
     orginal: String,
@@ -559,279 +1385,639 @@ fn format_helper(
     comma_interval: &Option<usize>,
     flag: &Option<RadixFlag>,
 ) -> Result<(), TildeError> {
-    let mut inner_buf = String::with_capacity(orginal.len());
-    if let Some(RadixFlag::Colon) = flag {
-        match comma_interval {
-            Some(n) => {
-                let commachar = match commachar {
-                    Some(c) => *c,
-                    None => ',',
-                };
-
-                if orginal.len() > *n {
-                    let mut edge = orginal.len() % n;
-                    if edge == 0 {
-                        edge = *n;
-                    };
-                    inner_buf.push_str(&orginal[0..edge]);
-                    loop {
-                        if edge >= orginal.len() {
-                            break;
-                        }
-                        inner_buf.push(commachar);
-                        inner_buf.push_str(&orginal[edge..edge + n]);
-                        edge += n;
-                    }
-                } else {
-                    inner_buf.push_str(&orginal);
-                }
+    let (sign, digits) = match orginal.strip_prefix('-') {
+        Some(d) => ("-", d),
+        None if matches!(flag, Some(RadixFlag::At | RadixFlag::AtColon)) => ("+", orginal.as_str()),
+        None => ("", orginal.as_str()),
+    };
+
+    let mut inner_buf = String::with_capacity(orginal.len() + 1);
+    inner_buf.push_str(sign);
+    match flag {
+        Some(RadixFlag::Colon | RadixFlag::AtColon) => {
+            let n = comma_interval.unwrap_or(3).max(1);
+            let commachar = commachar.unwrap_or(',');
+
+            let mut edge = digits.len() % n;
+            if edge == 0 {
+                edge = n.min(digits.len());
+            };
+            inner_buf.push_str(&digits[0..edge]);
+            while edge < digits.len() {
+                inner_buf.push(commachar);
+                inner_buf.push_str(&digits[edge..edge + n]);
+                edge += n;
             }
-            None => (),
         }
-    } else {
-        inner_buf = orginal
-    }
-
-    match mincol {
-        Some(n) => {
-            if *n > inner_buf.len() {
-                buf.push_str(
-                    &iter::repeat(padchar.unwrap_or(' '))
-                        .take(*n - inner_buf.len())
-                        .collect::<String>(),
-                );
-                buf.push_str(&inner_buf);
+        _ => inner_buf.push_str(digits),
+    }
+
+    pad_left(buf, &inner_buf, mincol, padchar);
+
+    Ok(())
+}
+
+/// format the integer by `~R`, `abs` is the absolute value
+fn format_radix(
+    buf: &mut String,
+    negative: bool,
+    abs: u128,
+    tkind: &TildeKind,
+) -> Result<(), TildeError> {
+    let (ra, mincol, padchar, commachar, comma_interval, flag) = match tkind {
+        TildeKind::Radix(params) => params,
+        _ => {
+            return Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to Radix",
+            ))
+        }
+    };
+
+    match (ra, flag) {
+        (Some(ra), _) if !(2..=36).contains(ra) => {
+            return Err(TildeError::new(
+                ErrorKind::OutOfRange,
+                format!("radix {} is not in 2..=36", ra),
+            ));
+        }
+        // ~xR, ~x:R is ~x,,,',,3:R
+        (Some(ra), _) => {
+            let s = format!(
+                "{}{}",
+                if negative { "-" } else { "" },
+                Radix::new(abs, *ra)
+            );
+            format_helper(buf, s, mincol, padchar, commachar, comma_interval, flag)?;
+        }
+        // ~@R and ~:@R
+        (None, Some(f @ (RadixFlag::At | RadixFlag::AtColon))) => {
+            if negative {
+                return Err(TildeError::new(
+                    ErrorKind::OutOfRange,
+                    format!("-{} cannot be revealed as roman numerals", abs),
+                ));
+            }
+            buf.push_str(&into_roman(abs, matches!(f, RadixFlag::AtColon))?);
+        }
+        // ~R and ~:R
+        (None, f) => {
+            if negative {
+                buf.push_str("negative ");
+            }
+            if matches!(f, Some(RadixFlag::Colon)) {
+                into_ordinal_english(abs, buf)
             } else {
-                buf.push_str(&inner_buf);
+                into_english(abs, buf)
             }
         }
-        None => buf.push_str(&inner_buf),
     }
 
     Ok(())
 }
 
-impl TildeKindRadix for isize {
-    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
-        match tkind {
-            TildeKind::Radix((ra, mincol, padchar, commachar, comma_interval, flag)) => {
-                match (ra, mincol, padchar, commachar, comma_interval, flag) {
-                    (ra, None, None, None, None, None) => {
-                        if ra.is_none() {
-                            // ~R
-                            if *self < 0 {
-                                buf.push_str("negative ");
-                                into_english(self.abs() as usize, buf)
-                            } else {
-                                into_english(self.abs() as usize, buf)
-                            }
-                        } else {
-                            // ~xR
-                            if *self < 0 {
-                                write!(buf, "-{}", Radix::new(-*self, ra.unwrap())).map_err(
-                                    |e| TildeError::new(ErrorKind::FormatError, e.to_string()),
-                                )?
-                            } else {
-                                write!(buf, "{}", Radix::new(*self, ra.unwrap())).map_err(|e| {
-                                    TildeError::new(ErrorKind::FormatError, e.to_string())
-                                })?
-                            }
-                        }
-                    }
-                    (ra, None, None, None, None, Some(RadixFlag::Colon)) => {
-                        if ra.is_none() {
-                            // ~:R
-                            if *self < 0 {
-                                buf.push_str("negative ");
-                                into_ordinal_english(self.abs() as usize, buf)
-                            } else {
-                                into_ordinal_english(self.abs() as usize, buf)
-                            }
-                        } else {
-                            // ~x:R
-                            // ~x:R == ~x,,,',,3:R
-                            if *self < 0 {
-                                buf.push('-');
-                                let s = Radix::new(-*self, ra.unwrap()).to_string();
-                                format_helper(buf, s, mincol, padchar, &Some(','), &Some(3), flag)?;
-                            } else {
-                                let s = Radix::new(*self, ra.unwrap()).to_string();
-                                format_helper(buf, s, mincol, padchar, &Some(','), &Some(3), flag)?;
-                            }
-                        }
-                    }
+multi_tilde_impl!(
+    TildeKindRadix,
+    [i8, i16, i32, i64, i128, isize],
+    self,
+    tkind,
+    buf,
+    {
+        #[allow(clippy::unnecessary_cast)]
+        let abs = self.unsigned_abs() as u128;
+        format_radix(buf, *self < 0, abs, tkind)
+    }
+);
 
-                    (None, None, None, None, None, Some(RadixFlag::At)) => {
-                        // ~@R
-                        if *self <= 0 {
-                            return Err(TildeError::new(
-                                ErrorKind::FormatError,
-                                "negative cannot be roman numerals",
-                            ));
-                        } else {
-                            buf.push_str(&into_roman(*self as usize)?);
-                        }
-                    }
+multi_tilde_impl!(
+    TildeKindRadix,
+    [u8, u16, u32, u64, u128, usize],
+    self,
+    tkind,
+    buf,
+    {
+        #[allow(clippy::unnecessary_cast)]
+        let abs = *self as u128;
+        format_radix(buf, false, abs, tkind)
+    }
+);
 
-                    (None, None, None, None, None, Some(RadixFlag::AtColon)) => {
-                        return Err(TildeError::new(
-                            ErrorKind::FormatError,
-                            "old Roman numeral haven't supported yet",
-                        ));
-                    }
+//========================================
+// TildeKindFloat
+//========================================
 
-                    (ra, _, _, _, _, _) => {
-                        if *self < 0 {
-                            buf.push('-');
-                            let s = Radix::new(-*self, ra.unwrap()).to_string();
-                            format_helper(
-                                buf,
-                                s,
-                                mincol,
-                                padchar,
-                                commachar,
-                                comma_interval,
-                                flag,
-                            )?;
-                        } else {
-                            let s = Radix::new(*self, ra.unwrap()).to_string();
-                            format_helper(
-                                buf,
-                                s,
-                                mincol,
-                                padchar,
-                                commachar,
-                                comma_interval,
-                                flag,
-                            )?;
-                        }
-                    }
+/// The decimal digits of a finite float, the value is `0.d1d2d3... * 10^exp`
+///
+/// The digits are the shortest ones that can round trip to the same float
+/// (what `Display` shows), and the rounding is half up on these digits.
+/// So `~,2F` of `2.675` is `2.68` as people read it. The digits of the integers are exact.
+#[derive(Debug, Clone, PartialEq)]
+struct FloatDigits {
+    negative: bool,
+    digits: Vec<u8>, // ascii digits without the trailing zeros, empty is zero
+    exp: isize,
+}
+
+impl FloatDigits {
+    fn new(n: impl std::fmt::LowerExp) -> Self {
+        // like "-1.2345e3"
+        let s = format!("{:e}", n);
+        let negative = s.starts_with('-');
+        let (mantissa, exp) = s.trim_start_matches('-').split_once('e').unwrap();
+
+        let mut digits = mantissa.bytes().filter(|b| *b != b'.').collect::<Vec<_>>();
+        while digits.last() == Some(&b'0') {
+            digits.pop();
+        }
+
+        let exp = if digits.is_empty() {
+            0
+        } else {
+            exp.parse::<isize>().unwrap() + 1
+        };
+
+        Self {
+            negative,
+            digits,
+            exp,
+        }
+    }
+
+    /// multiply 10^k
+    fn scale(&mut self, k: isize) {
+        if !self.digits.is_empty() {
+            self.exp += k
+        }
+    }
+
+    /// how many digits after the point
+    fn frac_len(&self) -> usize {
+        (self.digits.len() as isize - self.exp).max(0) as usize
+    }
+
+    /// round half up to `frac` digits after the point
+    fn round_at(&mut self, frac: isize) {
+        let keep = self.exp + frac;
+        if keep < 0 {
+            self.digits.clear();
+            self.exp = 0;
+            return;
+        }
+
+        let keep = keep as usize;
+        if keep >= self.digits.len() {
+            return;
+        }
+
+        let up = self.digits[keep] >= b'5';
+        self.digits.truncate(keep);
+        if up {
+            let mut i = keep;
+            loop {
+                if i == 0 {
+                    self.digits.insert(0, b'1');
+                    self.exp += 1;
+                    break;
+                }
+                i -= 1;
+                if self.digits[i] == b'9' {
+                    self.digits[i] = b'0';
+                } else {
+                    self.digits[i] += 1;
+                    break;
                 }
-            }
-            _ => {
-                return Err(
-                    TildeError::new(ErrorKind::RevealError, "cannot format to Radix").into(),
-                )
             }
         }
-        Ok(())
+
+        while self.digits.last() == Some(&b'0') {
+            self.digits.pop();
+        }
+        if self.digits.is_empty() {
+            self.exp = 0;
+        }
     }
-}
 
-impl TildeKindRadix for i8 {
-    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
-        TildeKindRadix::format(&Into::<isize>::into(*self), tkind, buf)
+    /// digits before the point, empty if the number is less than 1
+    fn int_part(&self) -> String {
+        (0..self.exp.max(0) as usize)
+            .map(|i| *self.digits.get(i).unwrap_or(&b'0') as char)
+            .collect()
     }
-}
 
-impl TildeKindRadix for i16 {
-    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
-        TildeKindRadix::format(&Into::<isize>::into(*self), tkind, buf)
+    /// exactly `d` digits after the point
+    fn frac_part(&self, d: usize) -> String {
+        (0..d as isize)
+            .map(|i| match self.exp + i {
+                ind if ind < 0 => '0',
+                ind => *self.digits.get(ind as usize).unwrap_or(&b'0') as char,
+            })
+            .collect()
     }
 }
 
-impl TildeKindRadix for i32 {
-    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
-        TildeKindRadix::format(&(*self as isize), tkind, buf)
+/// push the `s` to buf, pad on left until the length is w
+fn pad_left(buf: &mut String, s: &str, w: &Option<usize>, padchar: &Option<char>) {
+    let len = s.chars().count();
+    if let Some(w) = w {
+        if *w > len {
+            buf.extend(iter::repeat_n(padchar.unwrap_or(' '), w - len));
+        }
     }
+    buf.push_str(s);
 }
 
-impl TildeKindRadix for i64 {
-    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
-        TildeKindRadix::format(&(*self as isize), tkind, buf)
+/// the `~w,d,k,overflowchar,padcharF`
+#[allow(clippy::type_complexity)] // the parameters of the TildeKind variant
+fn format_fixed(
+    buf: &mut String,
+    mut fd: FloatDigits,
+    (w, d, k, overflowchar, padchar, at): &(
+        Option<usize>,
+        Option<usize>,
+        Option<isize>,
+        Option<char>,
+        Option<char>,
+        bool,
+    ),
+) {
+    fd.scale(k.unwrap_or(0));
+    let sign = if fd.negative {
+        "-"
+    } else if *at {
+        "+"
+    } else {
+        ""
+    };
+
+    let frac_len = match (w, d) {
+        (_, Some(d)) => *d,
+        // as many digits as the width allows
+        (Some(w), None) => fd
+            .frac_len()
+            .max(1)
+            .min(w.saturating_sub(sign.len() + fd.exp.max(0) as usize + 1)),
+        (None, None) => fd.frac_len().max(1),
+    };
+    fd.round_at(frac_len as isize);
+
+    let int_part = fd.int_part();
+    let mut frac_part = fd.frac_part(frac_len);
+    if d.is_none() && w.is_some() {
+        // no trailing zero, except the single zero when the fraction is zero
+        frac_part.truncate(frac_part.trim_end_matches('0').len().max(1).min(frac_len));
     }
-}
 
-impl TildeKindRadix for i128 {
-    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
-        TildeKindRadix::format(&(*self as isize), tkind, buf)
+    let mut body = format!("{}{}.{}", sign, int_part, frac_part);
+    if int_part.is_empty() {
+        // the leading zero is optional when the width is tight
+        if w.is_none_or(|w| body.len() < w) {
+            body.insert(sign.len(), '0');
+        }
+    }
+
+    match (w, overflowchar) {
+        (Some(w), Some(oc)) if body.len() > *w => buf.extend(iter::repeat_n(*oc, *w)),
+        _ => pad_left(buf, &body, w, padchar),
     }
 }
 
-impl TildeKindRadix for usize {
-    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
+multi_tilde_impl!(TildeKindFloat, [f32, f64], self, tkind, buf, {
+    match tkind {
+        TildeKind::Float(params) => {
+            if self.is_finite() {
+                format_fixed(buf, FloatDigits::new(self), params);
+            } else {
+                pad_left(buf, &self.to_string(), &params.0, &params.4);
+            }
+            Ok(())
+        }
+        _ => Err(TildeError::new(
+            ErrorKind::RevealError,
+            "cannot format to Float",
+        )),
+    }
+});
+
+// the digits of the integer itself, not the f64 losing the precision
+multi_tilde_impl!(
+    TildeKindFloat,
+    [i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize],
+    self,
+    tkind,
+    buf,
+    {
         match tkind {
-            TildeKind::Radix((ra, mincol, padchar, commachar, comma_interval, flag)) => {
-                match (ra, mincol, padchar, commachar, comma_interval, flag) {
-                    (ra, None, None, None, None, None) => {
-                        if ra.is_none() {
-                            // ~R
-                            into_english(*self, buf)
-                        } else {
-                            // ~xR
-                            write!(buf, "{}", Radix::new(*self, ra.unwrap())).map_err(|e| {
-                                TildeError::new(ErrorKind::FormatError, e.to_string())
-                            })?
-                        }
-                    }
-                    (ra, None, None, None, None, Some(RadixFlag::Colon)) => {
-                        if ra.is_none() {
-                            // ~:R
-                            into_ordinal_english(*self, buf)
-                        } else {
-                            // ~x:R
-                            // ~x:R == ~x,,,',,3:R
-                            let s = Radix::new(*self, ra.unwrap()).to_string();
-                            format_helper(buf, s, mincol, padchar, &Some(','), &Some(3), flag)?;
-                        }
-                    }
+            TildeKind::Float(params) => {
+                format_fixed(buf, FloatDigits::new(self), params);
+                Ok(())
+            }
+            _ => Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to Float",
+            )),
+        }
+    }
+);
 
-                    (None, None, None, None, None, Some(RadixFlag::At)) => {
-                        // ~@R
-                        buf.push_str(&into_roman(*self as usize)?);
-                    }
+//========================================
+// TildeKindExponential
+//========================================
 
-                    (None, None, None, None, None, Some(RadixFlag::AtColon)) => {
-                        return Err(TildeError::new(
-                            ErrorKind::FormatError,
-                            "old Roman numeral haven't supported yet",
-                        ));
-                    }
+/// the `~w,d,e,k,overflowchar,padchar,exptcharE`
+///
+/// `marker` is the exponent char when the exptchar is omitted.
+/// Same as SBCL, `f32` uses `e` and `f64` uses `d`.
+#[allow(clippy::type_complexity)] // the parameters of the TildeKind variant
+fn format_exponential(
+    buf: &mut String,
+    mut fd: FloatDigits,
+    (w, d, e, k, overflowchar, padchar, exptchar, at): &(
+        Option<usize>,
+        Option<usize>,
+        Option<usize>,
+        Option<isize>,
+        Option<char>,
+        Option<char>,
+        Option<char>,
+        bool,
+    ),
+    marker: char,
+) {
+    let k = k.unwrap_or(1);
+    let sign = if fd.negative {
+        "-"
+    } else if *at {
+        "+"
+    } else {
+        ""
+    };
+    let expt_str = |expt: isize| {
+        let n = expt.unsigned_abs().to_string();
+        format!(
+            "{}{}{}{}",
+            exptchar.unwrap_or(marker),
+            if expt < 0 { '-' } else { '+' },
+            "0".repeat(e.unwrap_or(0).saturating_sub(n.len())),
+            n
+        )
+    };
+
+    // k digits before the point when k > 0, otherwise -k zeros after the point
+    let mut expt = if fd.digits.is_empty() { 0 } else { fd.exp - k };
+    fd.scale(-expt);
+
+    let frac_len = match (w, d) {
+        (_, Some(d)) if k > 0 => (*d as isize - k + 1).max(0) as usize,
+        (_, Some(d)) => *d,
+        // as many digits as the width allows
+        (Some(w), None) => fd
+            .frac_len()
+            .max(1)
+            .min(w.saturating_sub(sign.len() + k.max(0) as usize + 1 + expt_str(expt).len())),
+        (None, None) => fd.frac_len().max(1),
+    };
+    fd.round_at(frac_len as isize);
+    if !fd.digits.is_empty() && fd.exp > k {
+        // rounding carried to one more digit, like 9.99 to 10.0
+        expt += 1;
+        fd.scale(-1);
+    }
 
-                    (ra, _, _, _, _, _) => {
-                        let s = Radix::new(*self, ra.unwrap()).to_string();
-                        format_helper(buf, s, mincol, padchar, commachar, comma_interval, flag)?;
-                    }
+    let int_part = fd.int_part();
+    let mut frac_part = fd.frac_part(frac_len);
+    if d.is_none() && w.is_some() {
+        frac_part.truncate(frac_part.trim_end_matches('0').len().max(1).min(frac_len));
+    }
+
+    let expt_str = expt_str(expt);
+    let mut body = format!("{}{}.{}{}", sign, int_part, frac_part, expt_str);
+    if int_part.is_empty() {
+        // the leading zero is optional when the width is tight
+        if w.is_none_or(|w| body.len() < w) {
+            body.insert(sign.len(), '0');
+        }
+    }
+
+    // the exponent cannot fit in e digits
+    let expt_overflow = e.is_some_and(|e| expt.unsigned_abs().to_string().len() > e);
+    match (w, overflowchar) {
+        (Some(w), Some(oc)) if expt_overflow || body.len() > *w => {
+            buf.extend(iter::repeat_n(*oc, *w))
+        }
+        _ => pad_left(buf, &body, w, padchar),
+    }
+}
+
+impl TildeKindExponential for f32 {
+    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
+        match tkind {
+            TildeKind::Exponential(params) => {
+                if self.is_finite() {
+                    format_exponential(buf, FloatDigits::new(self), params, 'e');
+                } else {
+                    pad_left(buf, &self.to_string(), &params.0, &params.5);
                 }
+                Ok(())
             }
-            _ => {
-                return Err(
-                    TildeError::new(ErrorKind::RevealError, "cannot format to Radix").into(),
-                )
-            }
+            _ => Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to Exponential",
+            )),
         }
-        Ok(())
     }
 }
 
-impl TildeKindRadix for u8 {
+impl TildeKindExponential for f64 {
     fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
-        TildeKindRadix::format(&(*self as usize), tkind, buf)
+        match tkind {
+            TildeKind::Exponential(params) => {
+                if self.is_finite() {
+                    format_exponential(buf, FloatDigits::new(self), params, 'd');
+                } else {
+                    pad_left(buf, &self.to_string(), &params.0, &params.5);
+                }
+                Ok(())
+            }
+            _ => Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to Exponential",
+            )),
+        }
     }
 }
-impl TildeKindRadix for u16 {
-    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
-        TildeKindRadix::format(&(*self as usize), tkind, buf)
+
+//========================================
+// TildeKindGeneral
+//========================================
+
+/// the `~w,d,e,k,overflowchar,padchar,exptcharG`
+///
+/// print as `~ww,dd,,overflowchar,padcharF` with `ee` spaces after it
+/// when the number fits, otherwise as `~E`
+#[allow(clippy::type_complexity)] // the parameters of the TildeKind variant
+fn format_general(
+    buf: &mut String,
+    fd: FloatDigits,
+    params: &(
+        Option<usize>,
+        Option<usize>,
+        Option<usize>,
+        Option<isize>,
+        Option<char>,
+        Option<char>,
+        Option<char>,
+        bool,
+    ),
+    marker: char,
+) {
+    let (w, d, e, _, overflowchar, padchar, _, at) = params;
+    let n = fd.exp;
+    let ee = e.map_or(4, |e| e + 2);
+    let ww = w.map(|w| w.saturating_sub(ee));
+    let d = d.map_or_else(
+        || (fd.digits.len().max(1) as isize).max(n.min(7)),
+        |d| d as isize,
+    );
+    let dd = d - n;
+
+    if (0..=d).contains(&dd) {
+        format_fixed(
+            buf,
+            fd,
+            &(ww, Some(dd as usize), None, *overflowchar, *padchar, *at),
+        );
+        buf.extend(iter::repeat_n(' ', ee));
+    } else {
+        format_exponential(buf, fd, params, marker);
     }
 }
-impl TildeKindRadix for u32 {
+
+impl TildeKindGeneral for f32 {
     fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
-        TildeKindRadix::format(&(*self as usize), tkind, buf)
+        match tkind {
+            TildeKind::General(params) => {
+                if self.is_finite() {
+                    format_general(buf, FloatDigits::new(self), params, 'e');
+                } else {
+                    pad_left(buf, &self.to_string(), &params.0, &params.5);
+                }
+                Ok(())
+            }
+            _ => Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to General",
+            )),
+        }
     }
 }
 
-impl TildeKindRadix for u64 {
+impl TildeKindGeneral for f64 {
     fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
-        TildeKindRadix::format(&(*self as usize), tkind, buf)
+        match tkind {
+            TildeKind::General(params) => {
+                if self.is_finite() {
+                    format_general(buf, FloatDigits::new(self), params, 'd');
+                } else {
+                    pad_left(buf, &self.to_string(), &params.0, &params.5);
+                }
+                Ok(())
+            }
+            _ => Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to General",
+            )),
+        }
     }
 }
 
-impl TildeKindRadix for u128 {
-    fn format(&self, tkind: &TildeKind, buf: &mut String) -> Result<(), TildeError> {
-        TildeKindRadix::format(&(*self as usize), tkind, buf)
+//========================================
+// TildeKindMonetary
+//========================================
+
+/// the `~d,n,w,padchar$`
+#[allow(clippy::type_complexity)] // the parameters of the TildeKind variant
+fn format_monetary(
+    buf: &mut String,
+    mut fd: FloatDigits,
+    (d, n, w, padchar, colon, at): &(
+        Option<usize>,
+        Option<usize>,
+        Option<usize>,
+        Option<char>,
+        bool,
+        bool,
+    ),
+) {
+    let d = d.unwrap_or(2);
+    fd.round_at(d as isize);
+
+    let sign = if fd.negative {
+        "-"
+    } else if *at {
+        "+"
+    } else {
+        ""
+    };
+
+    let int_part = fd.int_part();
+    let leading_zeros = n.unwrap_or(1).saturating_sub(int_part.len());
+    let body = format!(
+        "{}{}.{}",
+        "0".repeat(leading_zeros),
+        int_part,
+        fd.frac_part(d)
+    );
+
+    let padding = w.unwrap_or(0).saturating_sub(sign.len() + body.len());
+    let padding = iter::repeat_n(padchar.unwrap_or(' '), padding);
+    if *colon {
+        buf.push_str(sign);
+        buf.extend(padding);
+    } else {
+        buf.extend(padding);
+        buf.push_str(sign);
     }
+    buf.push_str(&body);
 }
 
+multi_tilde_impl!(TildeKindMonetary, [f32, f64], self, tkind, buf, {
+    match tkind {
+        TildeKind::Monetary(params) => {
+            if self.is_finite() {
+                format_monetary(buf, FloatDigits::new(self), params);
+            } else {
+                pad_left(buf, &self.to_string(), &params.2, &params.3);
+            }
+            Ok(())
+        }
+        _ => Err(TildeError::new(
+            ErrorKind::RevealError,
+            "cannot format to Monetary",
+        )),
+    }
+});
+
+// the digits of the integer itself, not the f64 losing the precision
+multi_tilde_impl!(
+    TildeKindMonetary,
+    [i8, i16, i32, i64, i128, u8, u16, u32, u64, u128, usize, isize],
+    self,
+    tkind,
+    buf,
+    {
+        match tkind {
+            TildeKind::Monetary(params) => {
+                format_monetary(buf, FloatDigits::new(self), params);
+                Ok(())
+            }
+            _ => Err(TildeError::new(
+                ErrorKind::RevealError,
+                "cannot format to Monetary",
+            )),
+        }
+    }
+);
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -856,6 +2042,11 @@ mod tests {
         let mut buf = String::new();
         into_english(2132314453234, &mut buf);
         assert_eq!(buf, String::from("two trillion one hundred thirty-two billion three hundred fourteen million four hundred fifty-three thousand two hundred thirty-four"));
+
+        let mut buf = String::new();
+        into_english(u128::MAX, &mut buf);
+        assert!(buf.starts_with("three hundred forty undecillion two hundred eighty-two decillion"));
+        assert!(buf.ends_with("two hundred eleven thousand four hundred fifty-five"));
     }
 
     #[test]
@@ -982,4 +2173,36 @@ mod tests {
         .is_ok());
         assert_eq!(buf, String::from("aaa17"));
     }
+
+    #[test]
+    fn test_float_digits() {
+        let fd = FloatDigits::new(1234.5_f64);
+        assert_eq!(fd.digits, b"12345".to_vec());
+        assert_eq!(fd.exp, 4);
+        assert_eq!(fd.int_part(), "1234");
+        assert_eq!(fd.frac_part(3), "500");
+
+        let fd = FloatDigits::new(0.1_f32);
+        assert_eq!(fd.digits, b"1".to_vec());
+        assert_eq!(fd.exp, 0);
+        assert_eq!(fd.frac_len(), 1);
+
+        let fd = FloatDigits::new(-0.0_f64);
+        assert!(fd.negative);
+        assert!(fd.digits.is_empty());
+
+        let mut fd = FloatDigits::new(0.0096_f64);
+        assert_eq!(fd.frac_part(4), "0096");
+        fd.round_at(2);
+        assert_eq!(fd.frac_part(2), "01");
+
+        let mut fd = FloatDigits::new(999.96_f64);
+        fd.round_at(1);
+        assert_eq!(fd.int_part(), "1000");
+        assert_eq!(fd.frac_part(1), "0");
+
+        let mut fd = FloatDigits::new(0.004_f64);
+        fd.round_at(1);
+        assert!(fd.digits.is_empty());
+    }
 }
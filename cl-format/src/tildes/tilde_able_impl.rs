@@ -1,4 +1,5 @@
 use super::*;
+use std::fmt::{self, Display};
 
 impl<'a, 'arg> TildeAble for Args<'a, 'arg> {
     fn len(&self) -> usize {
@@ -19,6 +20,27 @@ impl<'a, 'arg> TildeAble for Args<'a, 'arg> {
         }
     }
 
+    fn into_tildekind_exponential(&self) -> Option<&dyn TildeKindExponential> {
+        match self.pop() {
+            Some(a) => a.into_tildekind_exponential(),
+            None => None,
+        }
+    }
+
+    fn into_tildekind_general(&self) -> Option<&dyn TildeKindGeneral> {
+        match self.pop() {
+            Some(a) => a.into_tildekind_general(),
+            None => None,
+        }
+    }
+
+    fn into_tildekind_monetary(&self) -> Option<&dyn TildeKindMonetary> {
+        match self.pop() {
+            Some(a) => a.into_tildekind_monetary(),
+            None => None,
+        }
+    }
+
     fn into_tildekind_digit(&self) -> Option<&dyn TildeKindDigit> {
         match self.pop() {
             Some(a) => a.into_tildekind_digit(),
@@ -26,6 +48,27 @@ impl<'a, 'arg> TildeAble for Args<'a, 'arg> {
         }
     }
 
+    fn into_tildekind_binary(&self) -> Option<&dyn TildeKindBinary> {
+        match self.pop() {
+            Some(a) => a.into_tildekind_binary(),
+            None => None,
+        }
+    }
+
+    fn into_tildekind_octal(&self) -> Option<&dyn TildeKindOctal> {
+        match self.pop() {
+            Some(a) => a.into_tildekind_octal(),
+            None => None,
+        }
+    }
+
+    fn into_tildekind_hex(&self) -> Option<&dyn TildeKindHex> {
+        match self.pop() {
+            Some(a) => a.into_tildekind_hex(),
+            None => None,
+        }
+    }
+
     fn into_tildekind_va(&self) -> Option<&dyn TildeKindVa> {
         match self.pop() {
             Some(a) => a.into_tildekind_va(),
@@ -41,8 +84,28 @@ impl<'a, 'arg> TildeAble for Args<'a, 'arg> {
         Some(self)
     }
 
+    fn into_tildekind_params(&self) -> Option<&dyn TildeKindParams> {
+        Some(self)
+    }
+
+    fn into_tildekind_recursive(&self) -> Option<&dyn TildeKindRecursive> {
+        Some(self)
+    }
+
+    fn into_tildekind_plural(&self) -> Option<&dyn TildeKindPlural> {
+        Some(self)
+    }
+
+    fn into_tildekind_justify(&self) -> Option<&dyn TildeKindJustify> {
+        Some(self)
+    }
+
+    fn into_tildekind_caseconv(&self) -> Option<&dyn TildeKindCaseConv> {
+        Some(self)
+    }
+
     fn into_tildekind_loopend(&self) -> Option<&dyn TildeKindLoopEnd> {
-        None
+        Some(self)
     }
 
     fn into_tildekind_cond(&self) -> Option<&dyn TildeKindCond> {
@@ -88,6 +151,10 @@ impl TildeAble for Option<&dyn TildeAble> {
     fn into_tildekind_cond(&self) -> Option<&dyn TildeKindCond> {
         Some(self)
     }
+
+    fn into_tildekind_params(&self) -> Option<&dyn TildeKindParams> {
+        Some(self)
+    }
 }
 
 impl TildeAble for Vec<&dyn TildeAble> {
@@ -102,4 +169,68 @@ impl TildeAble for Vec<&dyn TildeAble> {
     fn into_tildekind_loop(&self) -> Option<&dyn TildeKindLoop> {
         Some(self)
     }
+
+    fn into_tildekind_vectilde(&self) -> Option<&dyn TildeKindVecTilde> {
+        Some(self)
+    }
+}
+
+impl<'a> TildeAble for ControlStr<'a> {
+    fn len(&self) -> usize {
+        1
+    }
+
+    fn into_tildekind_recursive(&self) -> Option<&dyn TildeKindRecursive> {
+        Some(self)
+    }
+}
+
+/// the control string of `~?` and `~@?`, parsed when it is revealed
+impl TildeAble for &str {
+    fn len(&self) -> usize {
+        1
+    }
+
+    fn into_tildekind_recursive(&self) -> Option<&dyn TildeKindRecursive> {
+        Some(self)
+    }
+}
+
+/// Reveal any `Display` type by `~a`, like `&Disp("str")` and `&Disp(&path.display())`.
+pub struct Disp<'a, T: ?Sized>(pub &'a T);
+
+/// same as the `Display` of the value, it is how `~a` of the list shows it
+impl<T: Display + ?Sized> Debug for Disp<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: Display + ?Sized> TildeAble for Disp<'_, T> {
+    fn len(&self) -> usize {
+        1
+    }
+
+    fn into_tildekind_va(&self) -> Option<&dyn TildeKindVa> {
+        Some(self)
+    }
+}
+
+/// Reveal any `Debug` type by `~s`, like `&Dbg(&path_buf)` and `&Dbg(&Some(1))`.
+pub struct Dbg<'a, T: ?Sized>(pub &'a T);
+
+impl<T: Debug + ?Sized> Debug for Dbg<'_, T> {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+impl<T: Debug + ?Sized> TildeAble for Dbg<'_, T> {
+    fn len(&self) -> usize {
+        1
+    }
+
+    fn into_tildekind_standard(&self) -> Option<&dyn TildeKindStandard> {
+        Some(self)
+    }
 }
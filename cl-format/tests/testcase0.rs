@@ -114,3 +114,105 @@ fn play_around_with_hands() {
         "print the list: <empty>;".to_string()
     );
 }
+
+#[test]
+fn control_str_at_compile_time() {
+    // all kinds of tildes are made at compile time the same as parsing at runtime
+    let cs = control_str!(
+        "~a ~5,2F ~E ~G ~$ ~2R ~:@R ~:D ~B ~O ~X ~S ~@C ~2* ~:* ~@* ~v,'0d ~@? ~:P \
+         ~10<a~;b~> ~:(x~) ~:^ ~3~ ~% ~& ~| ~4T ~#[a~;b~:;c~] ~:{~a~} ~2@{~a~:} ~
+         end"
+    );
+    assert_eq!(
+        cs,
+        &ControlStr::new(
+            "~a ~5,2F ~E ~G ~$ ~2R ~:@R ~:D ~B ~O ~X ~S ~@C ~2* ~:* ~@* ~v,'0d ~@? ~:P \
+             ~10<a~;b~> ~:(x~) ~:^ ~3~ ~% ~& ~| ~4T ~#[a~;b~:;c~] ~:{~a~} ~2@{~a~:} ~
+         end"
+        )
+        .unwrap()
+    );
+
+    // the same control_str! is made once
+    let f = || control_str!("~a");
+    assert!(std::ptr::eq(f(), f()));
+
+    // the control string from runtime is still parsed at runtime
+    let s = String::from("~a and ~a");
+    let a = cl_format!(s.as_str(), &1, &2);
+    assert_eq!(String::from("1 and 2"), a.unwrap());
+}
+
+#[test]
+fn args_checked_at_compile_time() {
+    // the type behind &dyn TildeAble is checked at runtime
+    let a = cl_format!("~a, ~d", tilde!(&1), tilde!(&2));
+    assert_eq!(String::from("1, 2"), a.unwrap());
+
+    let a = cl_format!("~d", tilde!(&'a'));
+    assert!(a
+        .unwrap_err()
+        .to_string()
+        .contains("cannot reveal to Digit"));
+    let a = cl_format!("~c", tilde!(&1));
+    assert!(a.unwrap_err().to_string().contains("cannot reveal to Char"));
+
+    // the args used by ~@{ are unknown at compile time
+    let s = String::from("list");
+    let a = cl_format!("~a: ~@{~a~^, ~}", &s, &1, &2);
+    assert_eq!(String::from("list: 1, 2"), a.unwrap());
+
+    // ~^ may end early
+    let a = cl_format!("~a~^ and ~a", &1);
+    assert_eq!(String::from("1"), a.unwrap());
+
+    // the clauses of ~[ take the rest args
+    let a = cl_format!("~[zero~;~:d~]", &1_usize, &2000);
+    assert_eq!(String::from("2,000"), a.unwrap());
+}
+
+#[test]
+fn display_and_debug_wrappers() {
+    use std::borrow::Cow;
+    use std::path::PathBuf;
+
+    let name = "cl-format";
+    let cow: Cow<str> = Cow::Borrowed("cow");
+    let path = PathBuf::from("/tmp");
+
+    assert_eq!(
+        String::from("cl-format, cow, /tmp"),
+        cl_format!(
+            "~a, ~a, ~a",
+            &Disp(name),
+            &Disp(&cow),
+            &Disp(&path.display())
+        )
+        .unwrap()
+    );
+    assert_eq!(
+        String::from("\"cl-format\" \"/tmp\" Some(1)"),
+        cl_format!("~s ~s ~s", &Dbg(name), &Dbg(&path), &Dbg(&Some(1))).unwrap()
+    );
+
+    // the padding is the same as other types
+    assert_eq!(
+        String::from("cl-format   |"),
+        cl_format!("~12a|", &Disp(name)).unwrap()
+    );
+
+    // the wrappers are temporaries, the array keeps them alive
+    let l = [tilde!(disp: &name), tilde!(dbg: &path), &1];
+    assert_eq!(
+        String::from("cl-format, \"/tmp\", 1"),
+        cl_format!("~{~a, ~s, ~a~}", &l.to_vec()).unwrap()
+    );
+    assert_eq!(
+        String::from("cl-format, \"/tmp\""),
+        cl_format!("~a, ~s", l[0], l[1]).unwrap()
+    );
+
+    // Disp is only for ~a
+    let cs = ControlStr::new("~s").unwrap();
+    assert!(cs.reveal(Args::new(vec![tilde!(disp: &name)])).is_err());
+}
@@ -94,3 +94,60 @@ fn test_custom_struct() {
         cl_format!("First: ~a; Second: ~d", &s, &s).unwrap()
     );
 }
+
+#[derive(Debug, TildeAble)]
+#[tilde(va = "Display", standard = "Debug", digit = a, float = c)]
+struct MyDerivedStruct {
+    a: usize,
+    b: String,
+    c: f64,
+}
+
+impl std::fmt::Display for MyDerivedStruct {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "a: {}, b: {}", self.a, self.b)
+    }
+}
+
+#[derive(Debug, TildeAble)]
+#[tilde(va = "Debug", hex = 0)]
+struct Id(u32);
+
+#[derive(Debug, TildeAble)]
+#[tilde(va = "Debug")]
+enum Color {
+    Red,
+}
+
+#[test]
+fn test_derived_struct() {
+    let s = MyDerivedStruct {
+        a: 1234,
+        b: "b".to_string(),
+        c: 2.71,
+    };
+
+    assert_eq!(
+        "a: 1234, b: b|MyDerivedStruct { a: 1234, b: \"b\", c: 2.71 }".to_string(),
+        cl_format!("~a|~s", &s, &s).unwrap()
+    );
+    assert_eq!(
+        "1,234 2.71".to_string(),
+        cl_format!("~:d ~,2f", &s, &s).unwrap()
+    );
+    assert_eq!(
+        "a: 1234, b: b     |".to_string(),
+        cl_format!("~18a|", &s).unwrap()
+    );
+
+    assert_eq!(
+        "Id(255) FF Red".to_string(),
+        cl_format!("~a ~x ~a", &Id(255), &Id(255), &Color::Red).unwrap()
+    );
+
+    let l = vec![tilde!(&Color::Red), &Id(1)];
+    assert_eq!(
+        "Red, Id(1)".to_string(),
+        cl_format!("~{~a~^, ~}", &l).unwrap()
+    );
+}